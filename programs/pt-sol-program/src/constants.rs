@@ -14,3 +14,6 @@ pub const STAKE_INFO_SEED: &[u8] = b"stake_info";
 
 #[constant]
 pub const THREAD_AUTHORITY_SEED: &[u8] = b"authority";
+
+#[constant]
+pub const LP_MINT_SEED: &[u8] = b"lp_mint";
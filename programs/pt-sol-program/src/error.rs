@@ -8,6 +8,26 @@ pub enum StakingError {
     NotStaked,
     #[msg("No tokens to stake")]
     NoTokens,
+    #[msg("The mint is not the staking mint of the staking pool")]
+    InvalidStakingMint,
+    #[msg("The mint is not the reward mint of the staking pool")]
+    InvalidRewardMint,
+    #[msg("The token account is not the reward vault of the staking pool")]
+    InvalidVault,
+    #[msg("The signer is not the authority of the staking pool")]
+    InvalidStakingAuthority,
+    #[msg("The pool is not the pool of the staking pool")]
+    InvalidPool,
+    #[msg("Cannot unstake more than the staked amount")]
+    InsufficientStake,
+    #[msg("No rewards to claim")]
+    NoRewards,
+    #[msg("Invalid lockup option")]
+    InvalidLockup,
+    #[msg("Staked tokens are locked until the end of the lockup")]
+    StakeLocked,
+    #[msg("Cannot add to a locked stake with a shorter lockup than the current one")]
+    ShorterLockup,
 }
 
 #[error_code]
@@ -42,4 +62,142 @@ pub enum SwapProgramError {
     /// The user proposed to pay 0 of an asset
     #[msg("A user cannot propose to pay 0 of an asset")]
     InvalidSwapZeroAmount,
+
+    /// The amount of the "receive" asset resolved by `r = f(p)` is lower than
+    /// the minimum amount the user is willing to accept, usually because the
+    /// pool balances moved after the user's quote was made
+    #[msg("The amount to receive is less than the requested minimum amount out")]
+    InvalidSwapSlippageExceeded,
+
+    /// The amount of the "pay" asset resolved by `p = f⁻¹(r)` for the requested
+    /// receive amount is greater than the maximum the user is willing to pay
+    #[msg("The amount to pay is greater than the requested maximum amount in")]
+    InvalidSwapMaxAmountInExceeded,
+
+    /// A deposit resolves to 0 LP shares, or a liquidity provider proposed to
+    /// burn 0 LP shares
+    #[msg("The deposit or withdrawal resolves to 0 liquidity pool shares")]
+    InvalidLiquidityZeroShares,
+
+    /// The token accounts provided for a withdrawal do not match the pool's
+    /// token account and mint of every asset stored in the `LiquidityPool` state
+    #[msg("The token accounts provided do not match the assets of the pool")]
+    InvalidLiquidityAccounts,
+
+    /// The signer is not the authority stored in the `LiquidityPool` state
+    #[msg("The signer is not the authority of the pool")]
+    InvalidPoolAuthority,
+
+    /// A deposit of an asset the pool does not hold yet lists it and mints no
+    /// LP shares, so it has to be acknowledged with `list_asset`; a deposit of
+    /// a listed asset must not set it
+    #[msg("The deposit does not match the list_asset flag")]
+    InvalidListing,
+
+    /// The proposed swap fee is greater than `LiquidityPool::MAX_FEE_BPS`, or
+    /// the proposed protocol share of the fee is greater than 100%
+    #[msg("The fee exceeds the maximum allowed fee")]
+    InvalidFee,
+
+    /// The signer is not the treasury stored in the `LiquidityPool` state
+    #[msg("The signer is not the treasury of the pool")]
+    InvalidPoolTreasury,
+
+    /// The signer is not the pending authority proposed by the current
+    /// authority of the `LiquidityPool`, or no transfer has been proposed
+    #[msg("The signer is not the pending authority of the pool")]
+    InvalidPendingPoolAuthority,
+
+    /// The pool has been paused by its authority, either completely or in
+    /// withdraw-only mode
+    #[msg("The pool is paused")]
+    PoolPaused,
+
+    /// An asset proposed to be delisted still has a tracked reserve, which
+    /// belongs to the liquidity providers
+    #[msg("Only assets with an empty pool reserve can be delisted")]
+    InvalidDelistNotEmpty,
+
+    /// The proposed weights do not match the pool's assets, or one of the
+    /// assets would fall below `LiquidityPool::MIN_WEIGHT_PERCENT`
+    #[msg("Invalid asset weights")]
+    InvalidWeights,
+
+    /// The instruction does not apply to the pool's curve: only StableSwap
+    /// pools have an amplification coefficient, and only constant-product
+    /// pools are priced with asset weights
+    #[msg("The instruction does not apply to the pool's curve")]
+    InvalidCurve,
+
+    /// The amplification coefficient is outside `LiquidityPool::MIN_AMP` and
+    /// `LiquidityPool::MAX_AMP`, or the ramp changes it too much or too fast
+    #[msg("Invalid amplification coefficient")]
+    InvalidAmp,
+
+    /// A StableSwap pool already holds two assets, which is all its
+    /// invariant covers, so a third asset cannot be listed
+    #[msg("A StableSwap pool holds at most two assets")]
+    InvalidStableSwapAssets,
+
+    /// The tick spacing of a concentrated-liquidity pool is zero or greater
+    /// than `ConcentratedPool::MAX_TICK_SPACING`
+    #[msg("Invalid tick spacing")]
+    InvalidTickSpacing,
+
+    /// The initial square-root price of a concentrated-liquidity pool is
+    /// outside the range of valid ticks
+    #[msg("Invalid square-root price")]
+    InvalidSqrtPrice,
+
+    /// The ticks of a position are not multiples of the pool's tick spacing,
+    /// are out of range, or the lower tick is not below the upper tick
+    #[msg("Invalid tick range")]
+    InvalidTickRange,
+
+    /// A tick array does not belong to the pool, does not contain the tick it
+    /// was given for, or the tick arrays of a swap are missing or out of order
+    #[msg("Invalid tick array")]
+    InvalidTickArray,
+
+    /// The liquidity change would exceed `ConcentratedPool::MAX_LIQUIDITY` or
+    /// remove more liquidity than the position holds
+    #[msg("Invalid liquidity amount")]
+    InvalidLiquidity,
+
+    /// The token amounts of a liquidity change are above the given maximums
+    /// or below the given minimums
+    #[msg("Liquidity slippage tolerance exceeded")]
+    InvalidLiquiditySlippageExceeded,
+
+    /// A position can only be closed once its liquidity is removed and its
+    /// fees are collected
+    #[msg("The position still holds liquidity or fees")]
+    InvalidPositionNotEmpty,
+
+    /// A swap route has fewer than two assets, visits an asset twice, or its
+    /// accounts do not match the pool and the payer's token accounts
+    #[msg("Invalid swap route")]
+    InvalidSwapRoute,
+
+    /// The TWAP window is zero, or the oracle has no observation old enough
+    /// to cover it that was made after both assets were last listed
+    #[msg("The oracle has no observation for the requested window")]
+    InvalidOracleWindow,
+
+    /// A mint is owned by neither the SPL Token nor the Token-2022 program
+    #[msg("The mint is not owned by a supported token program")]
+    InvalidTokenProgram,
+
+    /// A flash loan is for zero or more than the pool's reserve of the asset
+    #[msg("Invalid flash loan amount")]
+    InvalidFlashLoanAmount,
+
+    /// The borrower program of a flash loan is this program itself
+    #[msg("Invalid flash loan borrower program")]
+    InvalidFlashLoanProgram,
+
+    /// The pool's token account holds less than its balance before the flash
+    /// loan plus the fee once the borrower program returns
+    #[msg("The flash loan and its fee were not repaid")]
+    InvalidFlashLoanNotRepaid,
 }
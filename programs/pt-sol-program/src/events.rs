@@ -0,0 +1,143 @@
+//! Events emitted by the program, so indexers can follow every swap, deposit
+//! and stake without diffing token balances
+use anchor_lang::prelude::*;
+
+/// A swap in a `LiquidityPool`. A `swap_route` emits one event per hop
+#[event]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub pay_mint: Pubkey,
+    pub receive_mint: Pubkey,
+    pub pay_amount: u64,
+    pub receive_amount: u64,
+    /// 支付的数额中的交易手续费
+    pub fee_amount: u64,
+    /// 交易后池子中支付资产的余额(不包括协议手续费)
+    pub pay_reserve: u64,
+    /// 交易后池子中接收资产的余额(不包括协议手续费)
+    pub receive_reserve: u64,
+}
+
+/// A single-asset deposit into a `LiquidityPool`
+#[event]
+pub struct DepositEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// 铸造的LP份额
+    pub shares: u64,
+    /// 存入后池子中该资产的余额(不包括协议手续费)
+    pub reserve: u64,
+}
+
+/// A proportional withdrawal from a `LiquidityPool`
+#[event]
+pub struct WithdrawEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    /// 销毁的LP份额
+    pub shares: u64,
+    /// 取回的每一种资产的数额, 与`pool.assets`一一对应
+    pub amounts: Vec<u64>,
+}
+
+/// A flash loan from a `LiquidityPool`, emitted once it is repaid
+#[event]
+pub struct FlashLoanEvent {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// 借款人支付的手续费
+    pub fee: u64,
+}
+
+/// A swap in a `ConcentratedPool`
+#[event]
+pub struct ConcentratedSwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    /// 支付资产A换资产B时为`true`
+    pub a_to_b: bool,
+    pub pay_amount: u64,
+    pub receive_amount: u64,
+    /// 交易后价格的平方根, Q64.64
+    pub sqrt_price_x64: u128,
+    /// 交易后价格所在的tick
+    pub tick_current: i32,
+}
+
+/// Liquidity added to or removed from a concentrated-liquidity position
+#[event]
+pub struct ModifyLiquidityEvent {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    /// 流动性的变化, 存入为正, 取回为负
+    pub liquidity_delta: i128,
+    /// 存入或取回的资产A的数额(pool一侧)
+    pub amount_a: u64,
+    /// 存入或取回的资产B的数额(pool一侧)
+    pub amount_b: u64,
+}
+
+/// Swap fees collected from a concentrated-liquidity position
+#[event]
+pub struct CollectFeesEvent {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct StakeEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+    /// 锁仓结束的slot
+    pub lock_end_slot: u64,
+    /// 锁仓的奖励倍数, 以基点表示
+    pub multiplier_bps: u16,
+}
+
+#[event]
+pub struct UnstakeEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// 从vault转出的stake奖励
+    pub reward: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClaimRewardsEvent {
+    pub user: Pubkey,
+    /// 奖励的铸币
+    pub mint: Pubkey,
+    /// 从vault转出的stake奖励
+    pub reward: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct InitTokenEvent {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct MintTokensEvent {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
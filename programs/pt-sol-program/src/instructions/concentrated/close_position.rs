@@ -0,0 +1,26 @@
+//! Instruction: ClosePosition
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 关闭一个已经取回全部流动性并领取了手续费的仓位, 租金退还给owner
+pub fn close_position(_ctx: Context<ClosePosition>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    /// 流动性仓位
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        constraint = position.is_empty() @ SwapProgramError::InvalidPositionNotEmpty,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 仓位的所有者
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
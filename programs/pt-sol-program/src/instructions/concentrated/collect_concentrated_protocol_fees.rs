@@ -0,0 +1,114 @@
+//! Instruction: CollectConcentratedProtocolFees
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::*;
+use crate::state::*;
+
+/// treasury领取集中流动性池子中两种资产累计的协议手续费
+pub fn collect_concentrated_protocol_fees(
+    ctx: Context<CollectConcentratedProtocolFees>,
+) -> Result<()> {
+    let amounts = (
+        ctx.accounts.pool.protocol_fees_a,
+        ctx.accounts.pool.protocol_fees_b,
+    );
+    ctx.accounts.pool.protocol_fees_a = 0;
+    ctx.accounts.pool.protocol_fees_b = 0;
+
+    // (铸币账户, pool的Token账户, treasury的Token账户, 数额, Token程序)
+    let transfers = [
+        (
+            &ctx.accounts.mint_a,
+            &ctx.accounts.pool_token_account_a,
+            &ctx.accounts.treasury_token_account_a,
+            amounts.0,
+            &ctx.accounts.token_program_a,
+        ),
+        (
+            &ctx.accounts.mint_b,
+            &ctx.accounts.pool_token_account_b,
+            &ctx.accounts.treasury_token_account_b,
+            amounts.1,
+            &ctx.accounts.token_program_b,
+        ),
+    ];
+    for (mint, pool_token_account, treasury_token_account, amount, token_program) in transfers {
+        if amount > 0 {
+            process_transfer_from_pool(
+                pool_token_account,
+                treasury_token_account,
+                mint,
+                amount,
+                &ctx.accounts.pool,
+                token_program,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectConcentratedProtocolFees<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        mut,
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = treasury @ SwapProgramError::InvalidPoolTreasury,
+        constraint = pool.allows_withdrawals() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// 资产A的铸币账户
+    #[account(address = pool.mint_a, mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 资产B的铸币账户
+    #[account(address = pool.mint_b, mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// pool存放资产A的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// pool存放资产B的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// treasury接收资产A协议手续费的Token账户
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::token_program = token_program_a,
+    )]
+    pub treasury_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// treasury接收资产B协议手续费的Token账户
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program_b,
+    )]
+    pub treasury_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 池子的treasury
+    pub treasury: Signer<'info>,
+
+    /// 资产A的Token程序, SPL Token或Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+
+    /// 资产B的Token程序, SPL Token或Token-2022
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,28 @@
+//! Instruction: CollectFees
+use anchor_lang::prelude::*;
+
+use crate::events::CollectFeesEvent;
+use crate::instructions::ModifyLiquidity;
+
+/// 结算并领取仓位累计的手续费
+pub fn collect_fees(ctx: Context<ModifyLiquidity>) -> Result<()> {
+    // 不改变流动性, 只结算到目前为止的手续费
+    ctx.accounts.modify_position(0)?;
+
+    let position = &mut ctx.accounts.position;
+    let amounts = (position.fees_owed_a, position.fees_owed_b);
+    position.fees_owed_a = 0;
+    position.fees_owed_b = 0;
+
+    ctx.accounts.process_transfers(amounts, false)?;
+
+    emit!(CollectFeesEvent {
+        pool: ctx.accounts.pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        amount_a: amounts.0,
+        amount_b: amounts.1,
+    });
+
+    Ok(())
+}
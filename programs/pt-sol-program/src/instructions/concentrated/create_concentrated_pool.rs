@@ -0,0 +1,95 @@
+//! Instruction: CreateConcentratedPool
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::error::*;
+use crate::state::*;
+
+/// Create a new concentrated-liquidity pool for `mint_a` and `mint_b`, keyed
+/// by `pool_id`, starting at the square-root price `sqrt_price_x64` (Q64.64).
+/// The payer becomes the pool's authority and treasury
+pub fn create_concentrated_pool(
+    ctx: Context<CreateConcentratedPool>,
+    pool_id: u64,
+    tick_spacing: u16,
+    fee_bps: u16,
+    sqrt_price_x64: u128,
+) -> Result<()> {
+    LiquidityPool::check_fee_bps(fee_bps)?;
+
+    ctx.accounts.pool.set_inner(ConcentratedPool::new(
+        pool_id,
+        (ctx.accounts.mint_a.key(), ctx.accounts.mint_b.key()),
+        ctx.accounts.payer.key(),
+        tick_spacing,
+        fee_bps,
+        sqrt_price_x64,
+        ctx.bumps.pool,
+    )?);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct CreateConcentratedPool<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        init,
+        space = ConcentratedPool::SPACE,
+        payer = payer,
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    /// 资产A的铸币账户
+    #[account(
+        mint::token_program = token_program_a,
+        constraint = !mint_a.key().eq(&mint_b.key()) @ SwapProgramError::InvalidSwapMatchingAssets
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+
+    /// 资产B的铸币账户
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: InterfaceAccount<'info, Mint>,
+
+    /// pool存放资产A的Token账户
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    /// pool存放资产B的Token账户
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_token_account_b: InterfaceAccount<'info, TokenAccount>,
+
+    /// Rent payer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+
+    /// 资产A的Token程序, SPL Token或Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+
+    /// 资产B的Token程序, SPL Token或Token-2022
+    pub token_program_b: Interface<'info, TokenInterface>,
+
+    /// AT程序
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
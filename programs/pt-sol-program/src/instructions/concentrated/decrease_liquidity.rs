@@ -0,0 +1,31 @@
+//! Instruction: DecreaseLiquidity
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::instructions::ModifyLiquidity;
+
+/// 从仓位取回`liquidity`, 收到的两种资产数额低于`amount_a_min`或`amount_b_min`时交易失败
+///
+/// 仓位累计的手续费不会一起转出, 需要用`collect_fees`领取
+pub fn decrease_liquidity(
+    ctx: Context<ModifyLiquidity>,
+    liquidity: u128,
+    amount_a_min: u64,
+    amount_b_min: u64,
+) -> Result<()> {
+    let liquidity_delta = i128::try_from(liquidity)
+        .ok()
+        .filter(|l| *l > 0)
+        .ok_or(SwapProgramError::InvalidLiquidity)?;
+
+    let amounts = ctx.accounts.modify_position(-liquidity_delta)?;
+    let (amount_a, amount_b) = ctx.accounts.owner_amounts(amounts, false)?;
+    if amount_a < amount_a_min || amount_b < amount_b_min {
+        return Err(SwapProgramError::InvalidLiquiditySlippageExceeded.into());
+    }
+
+    ctx.accounts.process_transfers(amounts, false)?;
+    ctx.accounts.emit_modify_liquidity(-liquidity_delta, amounts);
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! Instruction: IncreaseLiquidity
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::error::*;
+use crate::events::ModifyLiquidityEvent;
+use crate::state::*;
+
+/// 向仓位存入`liquidity`, 需要支付的两种资产数额超过`amount_a_max`或`amount_b_max`时交易失败
+///
+/// 池子以withdraw-only模式暂停时仍然可以取回流动性和领取手续费, 但不能存入
+pub fn increase_liquidity(
+    ctx: Context<ModifyLiquidity>,
+    liquidity: u128,
+    amount_a_max: u64,
+    amount_b_max: u64,
+) -> Result<()> {
+    if !ctx.accounts.pool.is_active() {
+        return Err(SwapProgramError::PoolPaused.into());
+    }
+
+    let liquidity_delta = i128::try_from(liquidity)
+        .ok()
+        .filter(|l| *l > 0)
+        .ok_or(SwapProgramError::InvalidLiquidity)?;
+
+    let amounts = ctx.accounts.modify_position(liquidity_delta)?;
+    let (amount_a, amount_b) = ctx.accounts.owner_amounts(amounts, true)?;
+    if amount_a > amount_a_max || amount_b > amount_b_max {
+        return Err(SwapProgramError::InvalidLiquiditySlippageExceeded.into());
+    }
+
+    ctx.accounts.process_transfers((amount_a, amount_b), true)?;
+    ctx.accounts.emit_modify_liquidity(liquidity_delta, amounts);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ModifyLiquidity<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        mut,
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.allows_withdrawals() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// 流动性仓位
+    #[account(mut, has_one = pool, has_one = owner)]
+    pub position: Box<Account<'info, Position>>,
+
+    /// 包含仓位下边界的tick数组
+    #[account(mut, has_one = pool @ SwapProgramError::InvalidTickArray)]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+
+    /// 包含仓位上边界的tick数组, 可以与`tick_array_lower`相同
+    #[account(mut, has_one = pool @ SwapProgramError::InvalidTickArray)]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+
+    /// 资产A的铸币账户
+    #[account(address = pool.mint_a, mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 资产B的铸币账户
+    #[account(address = pool.mint_b, mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// pool存放资产A的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// pool存放资产B的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// owner的资产A的Token账户
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = owner,
+        token::token_program = token_program_a,
+    )]
+    pub owner_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// owner的资产B的Token账户
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = owner,
+        token::token_program = token_program_b,
+    )]
+    pub owner_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 仓位的所有者
+    pub owner: Signer<'info>,
+
+    /// 资产A的Token程序, SPL Token或Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+
+    /// 资产B的Token程序, SPL Token或Token-2022
+    pub token_program_b: Interface<'info, TokenInterface>,
+
+    /// AT程序
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ModifyLiquidity<'info> {
+    /// 修改仓位的流动性并写回两个边界tick, 返回两种资产的数额
+    pub fn modify_position(&mut self, liquidity_delta: i128) -> Result<(u64, u64)> {
+        let tick_spacing = self.pool.tick_spacing;
+        let (tick_lower, tick_upper) = (self.position.tick_lower, self.position.tick_upper);
+
+        let mut lower = self.tick_array_lower.tick(tick_lower, tick_spacing)?;
+        let mut upper = self.tick_array_upper.tick(tick_upper, tick_spacing)?;
+        let amounts = self.pool.modify_position(
+            &mut self.position,
+            (&mut lower, &mut upper),
+            liquidity_delta,
+        )?;
+
+        store_ticks(
+            (&mut self.tick_array_lower, &mut self.tick_array_upper),
+            (tick_lower, lower),
+            (tick_upper, upper),
+            tick_spacing,
+        )?;
+
+        Ok(amounts)
+    }
+
+    /// 把pool一侧的两种资产数额换算成owner一侧的数额: 存入时加上转账手续费(owner需要支付的数额),
+    /// 取出时减去转账手续费(owner实际收到的数额)
+    pub fn owner_amounts(&self, amounts: (u64, u64), deposit: bool) -> Result<(u64, u64)> {
+        let owner_amount = |mint: &InterfaceAccount<'info, Mint>, amount: u64| -> Result<u64> {
+            if deposit {
+                amount
+                    .checked_add(determine_transfer_fee_inverse(mint, amount)?)
+                    .ok_or(SwapProgramError::InvalidArithmetic.into())
+            } else {
+                Ok(amount - determine_transfer_fee(mint, amount)?)
+            }
+        };
+        Ok((
+            owner_amount(&self.mint_a, amounts.0)?,
+            owner_amount(&self.mint_b, amounts.1)?,
+        ))
+    }
+
+    /// 在owner和pool之间转移两种资产, `deposit`表示存入pool
+    pub fn process_transfers(&self, amounts: (u64, u64), deposit: bool) -> Result<()> {
+        // (铸币账户, pool的Token账户, owner的Token账户, 数额, Token程序)
+        let transfers = [
+            (
+                &self.mint_a,
+                &self.pool_token_account_a,
+                &self.owner_token_account_a,
+                amounts.0,
+                &self.token_program_a,
+            ),
+            (
+                &self.mint_b,
+                &self.pool_token_account_b,
+                &self.owner_token_account_b,
+                amounts.1,
+                &self.token_program_b,
+            ),
+        ];
+        for (mint, pool_token_account, owner_token_account, amount, token_program) in transfers {
+            if amount == 0 {
+                continue;
+            }
+            if deposit {
+                process_transfer_to_pool(
+                    owner_token_account,
+                    pool_token_account,
+                    mint,
+                    amount,
+                    &self.owner,
+                    token_program,
+                )?;
+            } else {
+                process_transfer_from_pool(
+                    pool_token_account,
+                    owner_token_account,
+                    mint,
+                    amount,
+                    &self.pool,
+                    token_program,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 记录仓位流动性的变化, `amounts`为pool一侧的两种资产数额
+    pub fn emit_modify_liquidity(&self, liquidity_delta: i128, amounts: (u64, u64)) {
+        emit!(ModifyLiquidityEvent {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            owner: self.owner.key(),
+            liquidity_delta,
+            amount_a: amounts.0,
+            amount_b: amounts.1,
+        });
+    }
+}
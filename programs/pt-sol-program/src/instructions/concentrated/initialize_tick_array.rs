@@ -0,0 +1,63 @@
+//! Instruction: InitializeTickArray
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::math::tick_math;
+use crate::state::*;
+
+/// 创建从`start_tick_index`开始的`TickArray`, 任何人都可以支付租金创建
+///
+/// `start_tick_index`必须是`tick_spacing * TickArray::SIZE`的整数倍
+pub fn initialize_tick_array(
+    ctx: Context<InitializeTickArray>,
+    start_tick_index: i32,
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    if pool.tick_array_start(start_tick_index) != start_tick_index
+        || start_tick_index + pool.tick_array_span() <= tick_math::MIN_TICK
+        || start_tick_index > tick_math::MAX_TICK
+    {
+        return Err(SwapProgramError::InvalidTickArray.into());
+    }
+
+    ctx.accounts.tick_array.set_inner(TickArray {
+        pool: pool.key(),
+        start_tick_index,
+        ticks: [Tick::default(); TickArray::SIZE],
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    /// 新的tick数组
+    #[account(
+        init,
+        space = TickArray::SPACE,
+        payer = payer,
+        seeds = [
+            TickArray::SEED_PREFIX.as_bytes(),
+            pool.key().as_ref(),
+            start_tick_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+
+    /// Rent payer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,25 @@
+pub mod close_position;
+pub mod collect_concentrated_protocol_fees;
+pub mod collect_fees;
+pub mod create_concentrated_pool;
+pub mod decrease_liquidity;
+pub mod increase_liquidity;
+pub mod initialize_tick_array;
+pub mod open_position;
+pub mod pause_concentrated;
+pub mod set_concentrated_protocol_fee;
+pub mod swap_concentrated;
+pub mod unpause_concentrated;
+
+pub use close_position::*;
+pub use collect_concentrated_protocol_fees::*;
+pub use collect_fees::*;
+pub use create_concentrated_pool::*;
+pub use decrease_liquidity::*;
+pub use increase_liquidity::*;
+pub use initialize_tick_array::*;
+pub use open_position::*;
+pub use pause_concentrated::*;
+pub use set_concentrated_protocol_fee::*;
+pub use swap_concentrated::*;
+pub use unpause_concentrated::*;
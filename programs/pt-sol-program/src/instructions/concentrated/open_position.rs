@@ -0,0 +1,60 @@
+//! Instruction: OpenPosition
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 在`tick_lower`和`tick_upper`之间开一个空的流动性仓位, 由`increase_liquidity`存入流动性
+pub fn open_position(ctx: Context<OpenPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+    ctx.accounts.pool.check_tick_range(tick_lower, tick_upper)?;
+
+    ctx.accounts.position.set_inner(Position {
+        pool: ctx.accounts.pool.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower,
+        tick_upper,
+        liquidity: 0,
+        fee_growth_inside_a_last_x64: 0,
+        fee_growth_inside_b_last_x64: 0,
+        fees_owed_a: 0,
+        fees_owed_b: 0,
+        bump: ctx.bumps.position,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    /// 新的流动性仓位
+    #[account(
+        init,
+        space = Position::SPACE,
+        payer = owner,
+        seeds = [
+            Position::SEED_PREFIX.as_bytes(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            tick_lower.to_le_bytes().as_ref(),
+            tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// 仓位的所有者
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
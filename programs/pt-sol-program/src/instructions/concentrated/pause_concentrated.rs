@@ -0,0 +1,37 @@
+//! Instruction: PauseConcentrated
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 紧急暂停集中流动性池子, 只有池子的管理员可以调用
+///
+/// `withdraw_only`为`true`时仍然允许取回流动性和领取手续费,
+/// 否则所有转移资产的指令都会失败
+pub fn pause_concentrated(
+    ctx: Context<UpdateConcentratedPoolStatus>,
+    withdraw_only: bool,
+) -> Result<()> {
+    ctx.accounts.pool.status = if withdraw_only {
+        PoolStatus::WithdrawOnly
+    } else {
+        PoolStatus::Paused
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConcentratedPoolStatus<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        mut,
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+    )]
+    pub pool: Account<'info, ConcentratedPool>,
+
+    /// 池子的管理员
+    pub authority: Signer<'info>,
+}
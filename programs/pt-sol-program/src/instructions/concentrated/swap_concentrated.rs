@@ -0,0 +1,170 @@
+//! Instruction: SwapConcentrated
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::error::*;
+use crate::events::ConcentratedSwapEvent;
+use crate::state::*;
+
+/// 在集中流动性池子中用`amount_in`的资产A换资产B(`a_to_b`), 或者反过来
+///
+/// `remaining_accounts`按交易方向给出价格会经过的`TickArray`, 第一个包含当前价格。
+/// 收到的数额低于`min_amount_out`时交易失败(滑点保护)
+pub fn swap_concentrated<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapConcentrated<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    a_to_b: bool,
+) -> Result<()> {
+    // Make sure the amount is not zero
+    if amount_in == 0 {
+        return Err(SwapProgramError::InvalidSwapZeroAmount.into());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+
+    // 同一个tick数组出现两次时, 未使用的副本会覆盖修改
+    let mut tick_arrays: Vec<Account<TickArray>> = Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        let tick_array = Account::<TickArray>::try_from(info)?;
+        if !info.is_writable
+            || tick_array.pool != pool.key()
+            || tick_arrays.iter().any(|t| t.key() == info.key())
+        {
+            return Err(SwapProgramError::InvalidTickArray.into());
+        }
+        tick_arrays.push(tick_array);
+    }
+
+    // (铸币账户, pool的Token账户, payer的Token账户, Token程序)
+    let a = (
+        &ctx.accounts.mint_a,
+        &ctx.accounts.pool_token_account_a,
+        &ctx.accounts.payer_token_account_a,
+        &ctx.accounts.token_program_a,
+    );
+    let b = (
+        &ctx.accounts.mint_b,
+        &ctx.accounts.pool_token_account_b,
+        &ctx.accounts.payer_token_account_b,
+        &ctx.accounts.token_program_b,
+    );
+    let (
+        (pay_mint, pool_pay, payer_pay, pay_token_program),
+        (receive_mint, pool_receive, payer_receive, receive_token_program),
+    ) = if a_to_b { (a, b) } else { (b, a) };
+
+    // 按pool实际收到的数额定价, 滑点按payer实际收到的数额检查
+    let pool_amount_in = amount_in - determine_transfer_fee(pay_mint, amount_in)?;
+    let amount_out = pool.swap(&mut tick_arrays, pool_amount_in, a_to_b)?;
+    let payer_amount_out = amount_out - determine_transfer_fee(receive_mint, amount_out)?;
+    if payer_amount_out == 0 {
+        return Err(SwapProgramError::InvalidSwapNotEnoughPay.into());
+    } else if payer_amount_out < min_amount_out {
+        return Err(SwapProgramError::InvalidSwapSlippageExceeded.into());
+    }
+
+    // 保存穿过的tick
+    for tick_array in tick_arrays.iter() {
+        tick_array.exit(&crate::ID)?;
+    }
+
+    process_transfer_to_pool(
+        payer_pay,
+        pool_pay,
+        pay_mint,
+        amount_in,
+        &ctx.accounts.payer,
+        pay_token_program,
+    )?;
+    process_transfer_from_pool(
+        pool_receive,
+        payer_receive,
+        receive_mint,
+        amount_out,
+        &ctx.accounts.pool,
+        receive_token_program,
+    )?;
+
+    emit!(ConcentratedSwapEvent {
+        pool: ctx.accounts.pool.key(),
+        user: ctx.accounts.payer.key(),
+        a_to_b,
+        pay_amount: amount_in,
+        receive_amount: amount_out,
+        sqrt_price_x64: ctx.accounts.pool.sqrt_price_x64,
+        tick_current: ctx.accounts.pool.tick_current,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapConcentrated<'info> {
+    /// Concentrated Liquidity Pool
+    #[account(
+        mut,
+        seeds = [ConcentratedPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Box<Account<'info, ConcentratedPool>>,
+
+    /// 资产A的铸币账户
+    #[account(address = pool.mint_a, mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 资产B的铸币账户
+    #[account(address = pool.mint_b, mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// pool存放资产A的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_a,
+    )]
+    pub pool_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// pool存放资产B的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program_b,
+    )]
+    pub pool_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// payer的资产A的Token账户
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program_a,
+    )]
+    pub payer_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// payer的资产B的Token账户
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub payer_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Token Program of asset A, SPL Token or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    /// Token Program of asset B, SPL Token or Token-2022
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
@@ -0,0 +1,12 @@
+//! Instruction: UnpauseConcentrated
+use anchor_lang::prelude::*;
+
+use crate::instructions::UpdateConcentratedPoolStatus;
+use crate::state::*;
+
+/// 恢复集中流动性池子的所有指令, 只有池子的管理员可以调用
+pub fn unpause_concentrated(ctx: Context<UpdateConcentratedPoolStatus>) -> Result<()> {
+    ctx.accounts.pool.status = PoolStatus::Active;
+
+    Ok(())
+}
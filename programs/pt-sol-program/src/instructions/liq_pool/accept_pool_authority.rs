@@ -0,0 +1,31 @@
+//! Instruction: AcceptPoolAuthority
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 转移管理员的第二步: 被提名的新管理员接受, 成为池子的管理员
+pub fn accept_pool_authority(ctx: Context<AcceptPoolAuthority>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    pool.authority = ctx.accounts.pending_authority.key();
+    pool.pending_authority = None;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptPoolAuthority<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.pending_authority == Some(pending_authority.key())
+            @ SwapProgramError::InvalidPendingPoolAuthority,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 被提名的新管理员
+    pub pending_authority: Signer<'info>,
+}
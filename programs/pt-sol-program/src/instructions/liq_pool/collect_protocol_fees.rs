@@ -0,0 +1,60 @@
+//! Instruction: CollectProtocolFees
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::*;
+use crate::state::*;
+
+/// treasury领取某一种资产累计的协议手续费
+pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Collect: (Mint, From, To)
+    let collect = (
+        &ctx.accounts.mint,
+        &ctx.accounts.pool_token_account,
+        &ctx.accounts.treasury_token_account,
+    );
+
+    pool.collect_protocol_fees(collect, &ctx.accounts.token_program)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = treasury @ SwapProgramError::InvalidPoolTreasury,
+        constraint = pool.allows_withdrawals() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 协议手续费对应资产的铸币账户
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// pool的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// treasury接收协议手续费的Token账户
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// 池子的treasury
+    pub treasury: Signer<'info>,
+
+    /// 资产的Token程序, SPL Token或Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,12 +1,37 @@
 //! Instruction: InitializePriceData
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
 
-use crate::state::*;
+use crate::{constants::LP_MINT_SEED, state::*};
+
+/// Create a new liquidity pool, keyed by `pool_id`
+///
+/// The payer becomes the pool's authority. `amp` is the amplification
+/// coefficient of a StableSwap pool and is ignored for the constant product
+pub fn create_pool(
+    ctx: Context<CreatePool>,
+    pool_id: u64,
+    fee_bps: u16,
+    curve: CurveType,
+    amp: u64,
+) -> Result<()> {
+    LiquidityPool::check_fee_bps(fee_bps)?;
+    let amp = match curve {
+        CurveType::ConstantProduct => 0,
+        CurveType::StableSwap => {
+            LiquidityPool::check_amp(amp)?;
+            amp
+        }
+    };
 
-/// Initialize the program by creating the liquidity pool
-pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
     // Initialize the new `LiquidityPool` state
     ctx.accounts.pool.set_inner(LiquidityPool::new(
+        pool_id,
+        ctx.accounts.lp_mint.key(),
+        ctx.accounts.payer.key(),
+        fee_bps,
+        curve,
+        amp,
         ctx.bumps.pool, // .expect("Failed to fetch bump for `pool`"),
     ));
 
@@ -14,19 +39,32 @@ pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
 }
 
 #[derive(Accounts)]
+#[instruction(pool_id: u64)]
 pub struct CreatePool<'info> {
     /// Liquidity Pool
     #[account(
         init,
         space = LiquidityPool::SPACE,
         payer = payer,
-        seeds = [LiquidityPool::SEED_PREFIX.as_bytes()],
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub pool: Account<'info, LiquidityPool>,
+    /// LP份额的铸币账户, 铸币权限为pool
+    #[account(
+        init,
+        payer = payer,
+        seeds = [LP_MINT_SEED, pool.key().as_ref()],
+        bump,
+        mint::decimals = LiquidityPool::LP_MINT_DECIMALS,
+        mint::authority = pool,
+    )]
+    pub lp_mint: Account<'info, Mint>,
     /// Rent payer
     #[account(mut)]
     pub payer: Signer<'info>,
     /// System Program: Required for creating the Liquidity Pool
     pub system_program: Program<'info, System>,
+    /// Token Program: Required for creating the LP mint
+    pub token_program: Program<'info, Token>,
 }
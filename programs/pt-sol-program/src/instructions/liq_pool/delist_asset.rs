@@ -0,0 +1,65 @@
+//! Instruction: DelistAsset
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::*;
+use crate::state::*;
+
+/// 从池子中下架一种资产, 只有池子的管理员可以调用
+///
+/// 池子中该资产属于流动性提供者的余额必须为0(例如流动性提供者在withdraw-only模式下取回了全部流动性),
+/// 剩下的协议手续费转移到treasury的Token账户, 然后关闭该Token账户并缩小池子账户, 多余的租金退还给管理员
+pub fn delist_asset(ctx: Context<DelistAsset>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Delist: (Mint, pool's Token Account, treasury's Token Account)
+    let delist = (
+        &ctx.accounts.mint,
+        &ctx.accounts.pool_token_account,
+        &ctx.accounts.treasury_token_account,
+    );
+
+    pool.remove_asset(delist, &ctx.accounts.authority, &ctx.accounts.token_program)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DelistAsset<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 要下架资产的铸币账户
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// pool的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// treasury接收剩余协议手续费的Token账户
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = pool.treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// 池子的管理员, 接收退还的租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// 资产的Token程序, SPL Token或Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,131 @@
+//! Instruction: FlashLoan
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke,
+    },
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::*;
+use crate::events::FlashLoanEvent;
+use crate::state::*;
+
+/// 闪电贷: 把池子中的`amount`借给borrower, 然后CPI调用`borrower_program`, 指令数据为`data`,
+/// 账户为`remaining_accounts`(保留原来的signer和writable标记)
+///
+/// `borrower_program`返回之后, pool的Token账户余额必须至少为借出前的余额加上手续费,
+/// 否则整个交易失败。手续费按池子的`fee_bps`向上取整, 其中协议的部分与swap一样记录在`protocol_fees`中
+pub fn flash_loan<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+    amount: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let index = pool.asset_index(&ctx.accounts.mint.key())?;
+
+    let balance = ctx.accounts.pool_token_account.amount;
+    if amount == 0 || amount > pool.reserve(index) {
+        return Err(SwapProgramError::InvalidFlashLoanAmount.into());
+    }
+    let fee = pool.flash_loan_fee(amount)?;
+
+    // pool's Token Account => borrower's Token Account
+    process_transfer_from_pool(
+        &ctx.accounts.pool_token_account,
+        &ctx.accounts.borrower_token_account,
+        &ctx.accounts.mint,
+        amount,
+        pool,
+        &ctx.accounts.token_program,
+    )?;
+
+    // borrower的程序使用借到的资产, 并在返回之前还款
+    let borrower_ix = Instruction {
+        program_id: ctx.accounts.borrower_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    invoke(&borrower_ix, ctx.remaining_accounts)?;
+
+    // 检查还款
+    let pool_token_account = &mut ctx.accounts.pool_token_account;
+    pool_token_account.reload()?;
+    let repaid_balance = pool_token_account.amount;
+    let required_balance = balance
+        .checked_add(fee)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    if repaid_balance < required_balance {
+        return Err(SwapProgramError::InvalidFlashLoanNotRepaid.into());
+    }
+
+    // 手续费中除了协议的部分都归流动性提供者, 多还的部分不计入储备
+    let protocol_fee = pool.accrue_protocol_fee(index, amount)?;
+    pool.add_reserve(index, fee.saturating_sub(protocol_fee))?;
+    pool.record_price(index)?;
+
+    emit!(FlashLoanEvent {
+        pool: pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        fee,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 借出资产的铸币账户
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// pool的Token账户
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program,
+    )]
+    pub pool_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// borrower接收借款的Token账户
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub borrower_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 借款人
+    pub borrower: Signer<'info>,
+
+    /// CHECK: 借款人指定的任意程序, 不能是本程序, 否则嵌套的指令会被外层保存的`pool`覆盖
+    #[account(
+        executable,
+        constraint = borrower_program.key() != crate::ID @ SwapProgramError::InvalidFlashLoanProgram,
+    )]
+    pub borrower_program: UncheckedAccount<'info>,
+
+    /// 资产的Token程序, SPL Token或Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+}
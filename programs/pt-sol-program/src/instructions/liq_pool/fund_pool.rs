@@ -2,15 +2,29 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use clockwork_sdk::state::Thread;
 
-use crate::{constants::THREAD_AUTHORITY_SEED, state::*};
+use crate::{constants::THREAD_AUTHORITY_SEED, error::*, state::*};
 
 /// 添加资产到流动性池子
-pub fn fund_pool(ctx: Context<FundPool>, amount: u64) -> Result<()> {
+///
+/// 池子中还没有的资产由管理员上架, 必须设置`list_asset`, 上架的存入不铸造LP份额
+///
+/// 存入的资产为SOL(native mint)时, 不足的部分从payer的lamports包装, 结束后关闭原来为空的payer的wSOL账户
+pub fn fund_pool(ctx: Context<FundPool>, amount: u64, list_asset: bool) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
+    let initial_amount = ctx.accounts.payer_token_account.amount;
+
+    process_wrap_sol(
+        &ctx.accounts.payer_token_account,
+        amount,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program,
+    )?;
 
     // Deposit: (From, To, amount)
     let deposit = (
@@ -20,10 +34,29 @@ pub fn fund_pool(ctx: Context<FundPool>, amount: u64) -> Result<()> {
         amount,
     );
 
+    // LP: (Mint, To, Locked)
+    let lp = (
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.payer_lp_token_account,
+        &ctx.accounts.pool_lp_token_account,
+    );
+
+    // Token Programs: (Asset, LP)
+    let token_programs = (&ctx.accounts.token_program, &ctx.accounts.lp_token_program);
+
     pool.fund(
         deposit,
+        list_asset,
+        lp,
         &ctx.accounts.payer,
         &ctx.accounts.system_program,
+        token_programs,
+    )?;
+
+    process_unwrap_sol(
+        &ctx.accounts.payer_token_account,
+        initial_amount,
+        &ctx.accounts.payer,
         &ctx.accounts.token_program,
     )?;
 
@@ -31,33 +64,63 @@ pub fn fund_pool(ctx: Context<FundPool>, amount: u64) -> Result<()> {
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64)]
+#[instruction(amount: u64, list_asset: bool)]
 pub struct FundPool<'info> {
     /// Liquidity Pool
     #[account(
         mut,
-        seeds = [LiquidityPool::SEED_PREFIX.as_bytes()],
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
         bump = pool.bump,
+        has_one = lp_mint,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
     )]
     pub pool: Account<'info, LiquidityPool>,
     /// LP池子的铸币账户
-    pub mint: Account<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
     /// LP池子的Token账户
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint,
         associated_token::authority = pool,
+        associated_token::token_program = token_program,
     )]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// payer的Token账户
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint,
         associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// LP份额的铸币账户
+    #[account(mut)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// payer接收LP份额的Token账户
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = lp_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = lp_token_program,
+    )]
+    pub payer_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// 池子自己的LP份额Token账户, 锁定第一次存入的`MINIMUM_LIQUIDITY`
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = lp_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = lp_token_program,
     )]
-    pub payer_token_account: Account<'info, TokenAccount>,
+    pub pool_lp_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// LP的供应
     #[account(mut)]
@@ -66,15 +129,19 @@ pub struct FundPool<'info> {
     /// 系统程序
     pub system_program: Program<'info, System>,
 
-    /// Token程序
-    pub token_program: Program<'info, Token>,
+    /// 资产的Token程序, SPL Token或Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// LP份额的Token程序
+    pub lp_token_program: Program<'info, Token>,
 
     /// AT程序
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     /// Verify that only this thread can execute the FundPool Instruction
+    /// 流动性提供者直接存入时不需要传入
     #[account(constraint = thread.authority.eq(&thread_authority.key()))]
-    pub thread: Account<'info, Thread>,
+    pub thread: Option<Account<'info, Thread>>,
 
     /// The Thread Admin
     /// The authority that was used as a seed to derive the thread address
@@ -0,0 +1,48 @@
+//! Instruction: GetTwap
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::math::exp;
+use crate::state::*;
+
+/// 返回最近`window_slots`个slot内以`quote_mint`计价的`base_mint`的时间加权平均价格(几何平均),
+/// 以WAD表示, 单位为两种资产的原始数额(不考虑decimals)
+///
+/// 从不晚于`当前slot - window_slots`的最新观测开始计算, 所以实际的区间可能略长于`window_slots`。
+/// 其中一种资产下架再上架之后, 它的累加器从0重新开始, 上架之前(包括上架的slot)的观测都不能使用
+pub fn get_twap(ctx: Context<GetTwap>, window_slots: u64) -> Result<u128> {
+    let pool = &ctx.accounts.pool;
+    let oracle = &ctx.accounts.oracle;
+    let base = pool.asset_index(&oracle.base_mint)?;
+    let quote = pool.asset_index(&oracle.quote_mint)?;
+
+    let slot = Clock::get()?.slot;
+    let observation = slot
+        .checked_sub(window_slots)
+        .filter(|_| window_slots > 0)
+        .and_then(|start| oracle.observation_before(start))
+        .filter(|observation| observation.slot > pool.listed_slot(base, quote))
+        .ok_or(SwapProgramError::InvalidOracleWindow)?;
+
+    // 区间内对数价格的平均值
+    let average = pool
+        .price_cumulative(base, quote, slot)
+        .wrapping_sub(observation.price_cumulative)
+        / (slot - observation.slot) as i128;
+
+    Ok(exp(average).ok_or(SwapProgramError::InvalidArithmetic)?)
+}
+
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    /// Liquidity Pool
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// TWAP预言机
+    #[account(has_one = pool)]
+    pub oracle: Box<Account<'info, PriceOracle>>,
+}
@@ -0,0 +1,71 @@
+//! Instruction: InitializeOracle
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 为池子中的一对资产创建TWAP预言机, 记录以`quote_mint`计价的`base_mint`的价格,
+/// 任何人都可以支付租金创建
+pub fn initialize_oracle(ctx: Context<InitializeOracle>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let base = pool.asset_index(&ctx.accounts.base_mint.key())?;
+    let quote = pool.asset_index(&ctx.accounts.quote_mint.key())?;
+
+    let slot = Clock::get()?.slot;
+    let observation = Observation {
+        slot,
+        price_cumulative: pool.price_cumulative(base, quote, slot),
+    };
+
+    ctx.accounts.oracle.set_inner(PriceOracle::new(
+        pool.key(),
+        (ctx.accounts.base_mint.key(), ctx.accounts.quote_mint.key()),
+        observation,
+        ctx.bumps.oracle,
+    ));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeOracle<'info> {
+    /// Liquidity Pool
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.curve == CurveType::ConstantProduct @ SwapProgramError::InvalidCurve,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 被计价的资产的铸币账户
+    #[account(
+        constraint = !base_mint.key().eq(&quote_mint.key()) @ SwapProgramError::InvalidSwapMatchingAssets
+    )]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    /// 计价的资产的铸币账户
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    /// 新的TWAP预言机
+    #[account(
+        init,
+        space = PriceOracle::SPACE,
+        payer = payer,
+        seeds = [
+            PriceOracle::SEED_PREFIX.as_bytes(),
+            pool.key().as_ref(),
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub oracle: Box<Account<'info, PriceOracle>>,
+
+    /// Rent payer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
@@ -1,7 +1,43 @@
+pub mod accept_pool_authority;
+pub mod collect_protocol_fees;
 pub mod create_pool;
+pub mod delist_asset;
+pub mod flash_loan;
 pub mod fund_pool;
+pub mod get_twap;
+pub mod initialize_oracle;
+pub mod pause;
+pub mod propose_pool_authority;
+pub mod quote_swap;
+pub mod ramp_amp;
+pub mod set_asset_weights;
+pub mod set_pool_fee;
+pub mod set_protocol_fee;
 pub mod swap;
+pub mod swap_exact_out;
+pub mod swap_route;
+pub mod unpause;
+pub mod update_oracle;
+pub mod withdraw_liquidity;
 
+pub use accept_pool_authority::*;
+pub use collect_protocol_fees::*;
 pub use create_pool::*;
+pub use delist_asset::*;
+pub use flash_loan::*;
 pub use fund_pool::*;
+pub use get_twap::*;
+pub use initialize_oracle::*;
+pub use pause::*;
+pub use propose_pool_authority::*;
+pub use quote_swap::*;
+pub use ramp_amp::*;
+pub use set_asset_weights::*;
+pub use set_pool_fee::*;
+pub use set_protocol_fee::*;
 pub use swap::*;
+pub use swap_exact_out::*;
+pub use swap_route::*;
+pub use unpause::*;
+pub use update_oracle::*;
+pub use withdraw_liquidity::*;
@@ -0,0 +1,34 @@
+//! Instruction: Pause
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 紧急暂停池子, 只有池子的管理员可以调用
+///
+/// `withdraw_only`为`true`时仍然允许用户取回流动性和unstake,
+/// 否则所有转移资产的指令都会失败
+pub fn pause(ctx: Context<UpdatePoolStatus>, withdraw_only: bool) -> Result<()> {
+    ctx.accounts.pool.status = if withdraw_only {
+        PoolStatus::WithdrawOnly
+    } else {
+        PoolStatus::Paused
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolStatus<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 池子的管理员
+    pub authority: Signer<'info>,
+}
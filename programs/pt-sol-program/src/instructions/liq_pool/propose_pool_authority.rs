@@ -0,0 +1,32 @@
+//! Instruction: ProposePoolAuthority
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 转移管理员的第一步: 当前的管理员提名新的管理员
+///
+/// 新的管理员调用`accept_pool_authority`之后转移才生效, 在此之前可以重新提名
+pub fn propose_pool_authority(
+    ctx: Context<ProposePoolAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    ctx.accounts.pool.pending_authority = Some(new_authority);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposePoolAuthority<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 池子当前的管理员
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,111 @@
+//! Instruction: QuoteSwap
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::*;
+use crate::math::{mul_div_ceil, mul_div_floor, WAD};
+use crate::state::*;
+
+/// The result of `quote_swap`, returned through the transaction's return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SwapQuote {
+    /// 支付`pay_amount`可以收到的数额, 与`swap`的结果完全一致
+    pub receive_amount: u64,
+    /// 支付的数额中的交易手续费
+    pub fee_amount: u64,
+    /// 价格影响, 以基点(bps)表示: 按交易前的边际价格扣除手续费后应收到的数额与实际收到的数额之差
+    pub price_impact_bps: u64,
+}
+
+/// 只读的swap报价, 不做任何转账, 结果通过return data返回
+///
+/// 用模拟交易调用即可得到与`swap`完全一致的数额, 客户端不需要自己实现定价公式
+pub fn quote_swap(ctx: Context<QuoteSwap>, pay_amount: u64) -> Result<SwapQuote> {
+    // Make sure the amount is not zero
+    if pay_amount == 0 {
+        return Err(SwapProgramError::InvalidSwapZeroAmount.into());
+    }
+
+    let pool = &ctx.accounts.pool;
+    // (资产的位置, 铸币的精度)
+    let receive = (
+        pool.asset_index(&ctx.accounts.receive_mint.key())?,
+        ctx.accounts.receive_mint.decimals,
+    );
+    let pay = (
+        pool.asset_index(&ctx.accounts.pay_mint.key())?,
+        ctx.accounts.pay_mint.decimals,
+    );
+
+    // 与`swap`一样, 按pool实际收到的数额定价, 再扣除接收的资产的转账手续费
+    let pool_pay_amount = pay_amount - determine_transfer_fee(&ctx.accounts.pay_mint, pay_amount)?;
+    let pool_receive_amount = pool.determine_receive(receive, pay, pool_pay_amount)?;
+    let receive_amount = pool_receive_amount
+        - determine_transfer_fee(&ctx.accounts.receive_mint, pool_receive_amount)?;
+    let fee_amount = pool.fee_amount(pool_pay_amount);
+
+    // 没有价格影响时应收到的数额, 转账手续费不算作价格影响
+    let spot_price = pool.determine_spot_price(receive, pay)?;
+    let ideal_amount = mul_div_floor((pool_pay_amount - fee_amount) as u128, spot_price, WAD)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let price_impact_bps = if ideal_amount > pool_receive_amount as u128 {
+        mul_div_ceil(
+            ideal_amount - pool_receive_amount as u128,
+            LiquidityPool::BPS_DENOMINATOR as u128,
+            ideal_amount,
+        )
+        .ok_or(SwapProgramError::InvalidArithmetic)? as u64
+    } else {
+        0
+    };
+
+    Ok(SwapQuote {
+        receive_amount,
+        fee_amount,
+        price_impact_bps,
+    })
+}
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    /// LP池子的账户
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 用于接收swap的铸币账户
+    #[account(
+        mint::token_program = receive_token_program,
+        constraint = !receive_mint.key().eq(&pay_mint.key()) @ SwapProgramError::InvalidSwapMatchingAssets
+    )]
+    pub receive_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 用于pool接收swap的Token账户
+    #[account(
+        associated_token::mint = receive_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = receive_token_program,
+    )]
+    pub pool_receive_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 用于发送swap的铸币用户
+    #[account(mint::token_program = pay_token_program)]
+    pub pay_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 用于pool发送swap的Token账户
+    #[account(
+        associated_token::mint = pay_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = pay_token_program,
+    )]
+    pub pool_pay_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 接收的资产的Token程序
+    pub receive_token_program: Interface<'info, TokenInterface>,
+
+    /// 支付的资产的Token程序
+    pub pay_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,32 @@
+//! Instruction: RampAmp
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 在`ramp_end_slot`之前将StableSwap池子的放大系数线性调整到`target_amp`,
+/// 只有池子的管理员可以调用
+///
+/// 调整从当前slot的放大系数开始, 至少持续`LiquidityPool::MIN_RAMP_SLOTS`,
+/// 且最多变化`LiquidityPool::MAX_AMP_CHANGE`倍
+pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_end_slot: u64) -> Result<()> {
+    let slot = Clock::get()?.slot;
+
+    ctx.accounts.pool.ramp_amp(target_amp, ramp_end_slot, slot)
+}
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+        constraint = pool.curve == CurveType::StableSwap @ SwapProgramError::InvalidCurve,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 池子的管理员
+    pub authority: Signer<'info>,
+}
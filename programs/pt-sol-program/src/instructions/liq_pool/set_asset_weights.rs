@@ -0,0 +1,36 @@
+//! Instruction: SetAssetWeights
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 在`ramp_end_slot`之前将池子中每一种资产的权重线性调整到`weights`, 按`assets`的顺序给出,
+/// 只有池子的管理员可以调用
+///
+/// 调整从当前slot的权重开始, 至少持续`LiquidityPool::MIN_RAMP_SLOTS`, 价格随权重逐渐变化,
+/// 而不是一次跳到新的价格。只有恒定乘积池子按权重定价, StableSwap池子不能调整权重
+pub fn set_asset_weights(
+    ctx: Context<SetAssetWeights>,
+    weights: Vec<u64>,
+    ramp_end_slot: u64,
+) -> Result<()> {
+    let slot = Clock::get()?.slot;
+
+    ctx.accounts.pool.ramp_weights(weights, ramp_end_slot, slot)
+}
+
+#[derive(Accounts)]
+pub struct SetAssetWeights<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+        constraint = pool.curve == CurveType::ConstantProduct @ SwapProgramError::InvalidCurve,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 池子的管理员
+    pub authority: Signer<'info>,
+}
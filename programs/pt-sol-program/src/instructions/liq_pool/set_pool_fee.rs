@@ -0,0 +1,29 @@
+//! Instruction: SetPoolFee
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 调整池子的交易手续费, 只有池子的管理员可以调用
+pub fn set_pool_fee(ctx: Context<SetPoolFee>, fee_bps: u16) -> Result<()> {
+    LiquidityPool::check_fee_bps(fee_bps)?;
+
+    ctx.accounts.pool.fee_bps = fee_bps;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPoolFee<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 池子的管理员
+    pub authority: Signer<'info>,
+}
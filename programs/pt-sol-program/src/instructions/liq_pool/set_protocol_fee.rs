@@ -0,0 +1,37 @@
+//! Instruction: SetProtocolFee
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+/// 设置交易手续费中归协议所有的部分以及领取协议手续费的treasury, 只有池子的管理员可以调用
+pub fn set_protocol_fee(
+    ctx: Context<SetProtocolFee>,
+    protocol_fee_bps: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    if protocol_fee_bps as u64 > LiquidityPool::BPS_DENOMINATOR {
+        return Err(SwapProgramError::InvalidFee.into());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_fee_bps = protocol_fee_bps;
+    pool.treasury = treasury;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = authority @ SwapProgramError::InvalidPoolAuthority,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 池子的管理员
+    pub authority: Signer<'info>,
+}
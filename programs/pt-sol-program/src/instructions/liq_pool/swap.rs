@@ -2,13 +2,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 use crate::error::*;
 use crate::state::*;
 
-pub fn swap(ctx: Context<Swap>, amount_to_swap: u64) -> Result<()> {
+/// 支付或接收的资产为SOL(native mint)时, payer的wSOL账户只是临时的: 不足的部分从payer的lamports包装,
+/// 交易结束后关闭该账户, 余额以lamports退还给payer。交易前已经持有wSOL的账户不会被关闭
+pub fn swap(ctx: Context<Swap>, amount_to_swap: u64, min_amount_out: u64) -> Result<()> {
     // Make sure the amount is not zero
     if amount_to_swap == 0 {
         return Err(SwapProgramError::InvalidSwapZeroAmount.into());
@@ -16,6 +18,20 @@ pub fn swap(ctx: Context<Swap>, amount_to_swap: u64) -> Result<()> {
 
     let pool = &mut ctx.accounts.pool;
 
+    // 交易前payer账户中的余额, 结束时只关闭原来为空的wSOL账户
+    let initial_amounts = (
+        ctx.accounts.payer_pay_token_account.amount,
+        ctx.accounts.payer_receive_token_account.amount,
+    );
+
+    process_wrap_sol(
+        &ctx.accounts.payer_pay_token_account,
+        amount_to_swap,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        &ctx.accounts.pay_token_program,
+    )?;
+
     // Receive: 接收swap
     // (Mint, From, To)
     let receive = (
@@ -33,13 +49,48 @@ pub fn swap(ctx: Context<Swap>, amount_to_swap: u64) -> Result<()> {
         amount_to_swap,
     );
 
+    // Token Programs: (Receive, Pay)
+    let token_programs = (
+        &ctx.accounts.receive_token_program,
+        &ctx.accounts.pay_token_program,
+    );
+
     pool.process_swap(
         receive,
         pay,
+        min_amount_out,
         &ctx.accounts.payer,
-        &ctx.accounts.token_program,
+        token_programs,
     )?;
 
+    process_unwrap_sol_accounts(ctx.accounts, initial_amounts)
+}
+
+/// 关闭payer在交易前为空的wSOL账户, `initial_amounts`为交易前 (支付, 接收) 账户的余额
+pub(crate) fn process_unwrap_sol_accounts(
+    accounts: &Swap<'_>,
+    initial_amounts: (u64, u64),
+) -> Result<()> {
+    for (token_account, initial_amount, token_program) in [
+        (
+            &accounts.payer_pay_token_account,
+            initial_amounts.0,
+            &accounts.pay_token_program,
+        ),
+        (
+            &accounts.payer_receive_token_account,
+            initial_amounts.1,
+            &accounts.receive_token_program,
+        ),
+    ] {
+        process_unwrap_sol(
+            token_account,
+            initial_amount,
+            &accounts.payer,
+            token_program,
+        )?;
+    }
+
     Ok(())
 }
 
@@ -48,24 +99,27 @@ pub struct Swap<'info> {
     /// LP池子的账户
     #[account(
         mut,
-        seeds = [LiquidityPool::SEED_PREFIX.as_bytes()],
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
         bump = pool.bump,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
     )]
     pub pool: Account<'info, LiquidityPool>,
 
     /// 用于接收swap的铸币账户
     #[account(
+        mint::token_program = receive_token_program,
         constraint = !receive_mint.key().eq(&pay_mint.key()) @ SwapProgramError::InvalidSwapMatchingAssets
     )]
-    pub receive_mint: Box<Account<'info, Mint>>,
+    pub receive_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// 用于pool接收swap的Token账户
     #[account(
         mut,
         associated_token::mint = receive_mint,
         associated_token::authority = pool,
+        associated_token::token_program = receive_token_program,
     )]
-    pub pool_receive_token_account: Box<Account<'info, TokenAccount>>,
+    pub pool_receive_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// 用于payer接收swap的Token用户
     #[account(
@@ -73,33 +127,39 @@ pub struct Swap<'info> {
         payer = payer,
         associated_token::mint = receive_mint,
         associated_token::authority = payer,
+        associated_token::token_program = receive_token_program,
     )]
-    pub payer_receive_token_account: Box<Account<'info, TokenAccount>>,
+    pub payer_receive_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// 用于发送swap的铸币用户
-    pub pay_mint: Box<Account<'info, Mint>>,
+    #[account(mint::token_program = pay_token_program)]
+    pub pay_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// 用于pool发送swap的Token账户
     #[account(
         mut,
         associated_token::mint = pay_mint,
         associated_token::authority = pool,
+        associated_token::token_program = pay_token_program,
     )]
-    pub pool_pay_token_account: Box<Account<'info, TokenAccount>>,
+    pub pool_pay_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// 用于payer发送swap的Token账户
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = pay_mint,
         associated_token::authority = payer,
+        associated_token::token_program = pay_token_program,
     )]
-    pub payer_pay_token_account: Box<Account<'info, TokenAccount>>,
+    pub payer_pay_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
-    /// Token Program: Required for transferring the assets between all token
-    /// accounts involved in the swap
-    pub token_program: Program<'info, Token>,
+    /// Token Program of the received asset, SPL Token or Token-2022
+    pub receive_token_program: Interface<'info, TokenInterface>,
+    /// Token Program of the paid asset, SPL Token or Token-2022
+    pub pay_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -0,0 +1,66 @@
+//! Instruction: SwapExactOut
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::instructions::{process_unwrap_sol_accounts, Swap};
+use crate::state::*;
+
+/// 以指定的接收数额进行swap, 需要支付的数额超过`max_amount_in`时交易失败
+///
+/// 与`swap`一样支持SOL: 支付SOL时最多从payer的lamports包装`max_amount_in`,
+/// 交易结束后关闭原来为空的wSOL账户, 没有用完的部分和收到的SOL以lamports退还给payer
+pub fn swap_exact_out(ctx: Context<Swap>, amount_out: u64, max_amount_in: u64) -> Result<()> {
+    // Make sure the amount is not zero
+    if amount_out == 0 {
+        return Err(SwapProgramError::InvalidSwapZeroAmount.into());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+
+    // 交易前payer账户中的余额, 结束时只关闭原来为空的wSOL账户
+    let initial_amounts = (
+        ctx.accounts.payer_pay_token_account.amount,
+        ctx.accounts.payer_receive_token_account.amount,
+    );
+
+    process_wrap_sol(
+        &ctx.accounts.payer_pay_token_account,
+        max_amount_in,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        &ctx.accounts.pay_token_program,
+    )?;
+
+    // Receive: 接收swap，内附数额
+    // (Mint, From, To, Amount)
+    let receive = (
+        ctx.accounts.receive_mint.as_ref(),
+        ctx.accounts.pool_receive_token_account.as_ref(),
+        ctx.accounts.payer_receive_token_account.as_ref(),
+        amount_out,
+    );
+
+    // Pay: 发送swap
+    // (Mint, From, To)
+    let pay = (
+        ctx.accounts.pay_mint.as_ref(),
+        ctx.accounts.payer_pay_token_account.as_ref(),
+        ctx.accounts.pool_pay_token_account.as_ref(),
+    );
+
+    // Token Programs: (Receive, Pay)
+    let token_programs = (
+        &ctx.accounts.receive_token_program,
+        &ctx.accounts.pay_token_program,
+    );
+
+    pool.process_swap_exact_out(
+        receive,
+        pay,
+        max_amount_in,
+        &ctx.accounts.payer,
+        token_programs,
+    )?;
+
+    process_unwrap_sol_accounts(ctx.accounts, initial_amounts)
+}
@@ -0,0 +1,108 @@
+//! Instruction: SwapRoute
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::error::*;
+use crate::state::*;
+
+/// 在同一个池子中沿着一条路径连续swap, 例如A→B→C, 收到的数额低于`min_amount_out`时交易失败
+///
+/// `remaining_accounts`按路径的顺序, 每一种资产给出两个账户: 铸币账户, 以及pool的Token账户。
+/// 路径至少包含两种资产, 且每一种资产只能出现一次
+pub fn swap_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    // Make sure the amount is not zero
+    if amount_in == 0 {
+        return Err(SwapProgramError::InvalidSwapZeroAmount.into());
+    }
+
+    let pool = &mut ctx.accounts.pool;
+
+    let path_len = ctx.remaining_accounts.len() / 2;
+    if path_len < 2 || path_len * 2 != ctx.remaining_accounts.len() {
+        return Err(SwapProgramError::InvalidSwapRoute.into());
+    }
+
+    // Path: (Mint, pool's Token Account)
+    let mut path: Vec<(InterfaceAccount<Mint>, InterfaceAccount<TokenAccount>)> =
+        Vec::with_capacity(path_len);
+    for accounts in ctx.remaining_accounts.chunks(2) {
+        let mint = InterfaceAccount::<Mint>::try_from(&accounts[0])?;
+        let pool_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+
+        let pool_token_address = get_associated_token_address_with_program_id(
+            &pool.key(),
+            &mint.key(),
+            accounts[0].owner,
+        );
+        if pool_token_account.key() != pool_token_address
+            || path.iter().any(|(m, _)| m.key() == mint.key())
+        {
+            return Err(SwapProgramError::InvalidSwapRoute.into());
+        }
+
+        path.push((mint, pool_token_account));
+    }
+
+    if ctx.accounts.payer_pay_token_account.mint != path[0].0.key()
+        || ctx.accounts.payer_receive_token_account.mint != path[path.len() - 1].0.key()
+    {
+        return Err(SwapProgramError::InvalidSwapRoute.into());
+    }
+
+    pool.process_swap_route(
+        &path,
+        (&ctx.accounts.payer_pay_token_account, amount_in),
+        (&ctx.accounts.payer_receive_token_account, min_amount_out),
+        &ctx.accounts.payer,
+        (
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_program_2022,
+        ),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// LP池子的账户
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// 用于payer发送路径上第一种资产的Token账户
+    #[account(
+        mut,
+        token::authority = payer,
+    )]
+    pub payer_pay_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 用于payer接收路径上最后一种资产的Token账户
+    #[account(
+        mut,
+        token::authority = payer,
+    )]
+    pub payer_receive_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Token Program: Required for transferring the SPL Token assets of the
+    /// route
+    pub token_program: Program<'info, Token>,
+    /// Token-2022 Program: Required for transferring the Token-2022 assets of
+    /// the route
+    pub token_program_2022: Program<'info, Token2022>,
+}
@@ -0,0 +1,12 @@
+//! Instruction: Unpause
+use anchor_lang::prelude::*;
+
+use crate::instructions::UpdatePoolStatus;
+use crate::state::*;
+
+/// 恢复池子的所有指令, 只有池子的管理员可以调用
+pub fn unpause(ctx: Context<UpdatePoolStatus>) -> Result<()> {
+    ctx.accounts.pool.status = PoolStatus::Active;
+
+    Ok(())
+}
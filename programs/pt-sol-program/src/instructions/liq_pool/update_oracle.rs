@@ -0,0 +1,37 @@
+//! Instruction: UpdateOracle
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+/// 把池子当前的累计价格记录到预言机中, 任何人都可以调用
+///
+/// 池子在每一次余额变化时都会累加价格, 这里只是保存一个观测点,
+/// 距离上一次观测不足`PriceOracle::MIN_OBSERVATION_INTERVAL`个slot时不做任何事
+pub fn update_oracle(ctx: Context<UpdateOracle>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let oracle = &mut ctx.accounts.oracle;
+    let base = pool.asset_index(&oracle.base_mint)?;
+    let quote = pool.asset_index(&oracle.quote_mint)?;
+
+    let slot = Clock::get()?.slot;
+    oracle.record(Observation {
+        slot,
+        price_cumulative: pool.price_cumulative(base, quote, slot),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracle<'info> {
+    /// Liquidity Pool
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// TWAP预言机
+    #[account(mut, has_one = pool)]
+    pub oracle: Box<Account<'info, PriceOracle>>,
+}
@@ -0,0 +1,101 @@
+//! Instruction: WithdrawLiquidity
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::error::*;
+use crate::state::*;
+
+/// 销毁LP份额, 按比例取回池子中的每一种资产
+///
+/// `remaining_accounts`按`pool.assets`的顺序, 每一种资产给出三个账户:
+/// 铸币账户, pool的Token账户, 以及接收该资产的Token账户
+pub fn withdraw_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawLiquidity<'info>>,
+    shares: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if ctx.remaining_accounts.len() != pool.assets.len() * 3 {
+        return Err(SwapProgramError::InvalidLiquidityAccounts.into());
+    }
+
+    // Withdrawals: (Mint, From, To)
+    let mut withdrawals = Vec::with_capacity(pool.assets.len());
+    for (mint, accounts) in pool.assets.iter().zip(ctx.remaining_accounts.chunks(3)) {
+        let mint_account = InterfaceAccount::<Mint>::try_from(&accounts[0])?;
+        let pool_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        let payer_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+
+        let token_program = accounts[0].owner;
+        if mint_account.key() != *mint
+            || pool_token_account.key()
+                != get_associated_token_address_with_program_id(&pool.key(), mint, token_program)
+            || payer_token_account.mint != *mint
+        {
+            return Err(SwapProgramError::InvalidLiquidityAccounts.into());
+        }
+
+        withdrawals.push((mint_account, pool_token_account, payer_token_account));
+    }
+
+    // LP: (Mint, From, Amount)
+    let lp = (
+        &ctx.accounts.lp_mint,
+        &ctx.accounts.payer_lp_token_account,
+        shares,
+    );
+
+    // Token Programs: (SPL Token, Token-2022)
+    let token_programs = (
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+    );
+
+    pool.withdraw(lp, &withdrawals, &ctx.accounts.payer, token_programs)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLiquidity<'info> {
+    /// Liquidity Pool
+    #[account(
+        mut,
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        has_one = lp_mint,
+        constraint = pool.allows_withdrawals() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// LP份额的铸币账户
+    #[account(mut)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// payer存放LP份额的Token账户
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub payer_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// LP的提供者
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Token程序, 也是LP份额的Token程序
+    pub token_program: Program<'info, Token>,
+
+    /// Token-2022程序
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// AT程序
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
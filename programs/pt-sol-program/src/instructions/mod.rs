@@ -1,7 +1,9 @@
+pub mod concentrated;
 pub mod liq_pool;
 pub mod staking;
 pub mod tokens;
 
+pub use concentrated::*;
 pub use liq_pool::*;
 pub use staking::*;
 pub use tokens::*;
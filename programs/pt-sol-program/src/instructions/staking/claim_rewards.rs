@@ -0,0 +1,118 @@
+use crate::{
+    constants::{STAKE_INFO_SEED, VAULT_SEED},
+    error::*,
+    events::ClaimRewardsEvent,
+    state::{LiquidityPool, StakeInfo, StakingPool},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+/// 领取stake累计的奖励, 不取回stake
+///
+/// 最多转出vault中现有的余额, 其余的保留在`reward_owed`中
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // 结算到当前slot为止的奖励
+    let stake_info = &mut ctx.accounts.stake_info_account;
+    let staking_pool = &mut ctx.accounts.staking_pool;
+    staking_pool.update(clock.slot)?;
+    staking_pool.settle(stake_info, clock.slot)?;
+
+    let reward = stake_info
+        .reward_owed
+        .min(ctx.accounts.token_vault_account.amount);
+    if reward == 0 {
+        return Err(StakingError::NoRewards.into());
+    }
+    stake_info.reward_owed -= reward;
+
+    let bump = ctx.bumps.token_vault_account;
+    let signer: &[&[&[u8]]] = &[&[VAULT_SEED, &[bump]]];
+
+    // vault => user的奖励账户
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.reward_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.token_vault_account.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                to: ctx.accounts.user_reward_token_account.to_account_info(),
+                authority: ctx.accounts.token_vault_account.to_account_info(),
+            },
+            signer,
+        ),
+        reward,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    emit!(ClaimRewardsEvent {
+        user: ctx.accounts.signer.key(),
+        mint: ctx.accounts.reward_mint.key(),
+        reward,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    // 全局的stake配置
+    #[account(
+        mut,
+        seeds = [StakingPool::SEED_PREFIX.as_bytes()],
+        bump = staking_pool.bump,
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // stake生成token奖励的账户
+    #[account(
+        mut,
+        seeds = [VAULT_SEED],
+        bump,
+        address = staking_pool.vault @ StakingError::InvalidVault,
+    )]
+    pub token_vault_account: InterfaceAccount<'info, TokenAccount>,
+
+    // stake_info账户，存放stake的信息(不是Token账户，这里是自定义的)
+    #[account(
+        mut,
+        seeds = [STAKE_INFO_SEED, signer.key.as_ref()],
+        bump,
+    )]
+    pub stake_info_account: Account<'info, StakeInfo>,
+
+    // stake对应的流动性池子, 暂停时(非withdraw-only模式)不能领取奖励
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        address = staking_pool.pool @ StakingError::InvalidPool,
+        constraint = pool.allows_withdrawals() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    // 奖励的铸币
+    #[account(address = staking_pool.reward_mint @ StakingError::InvalidRewardMint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    // user接收奖励的token账户
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = reward_token_program,
+    )]
+    pub user_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
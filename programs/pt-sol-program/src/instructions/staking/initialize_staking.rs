@@ -1,28 +1,89 @@
-use crate::constants::VAULT_SEED;
+use crate::{
+    constants::VAULT_SEED,
+    error::StakingError,
+    program::PtSolProgram,
+    state::{LiquidityPool, StakingPool},
+};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-pub fn initialize_staking(ctx: Context<InitializeStaking>) -> Result<()> {
+/// 初始化stake, 创建全局的`StakingPool`, `signer`成为管理员
+///
+/// 只有程序的升级权限(upgrade authority)可以初始化, 避免部署之后被其他人抢先成为管理员
+///
+/// stake对应的流动性池子`pool`记录在`StakingPool`中, 之后stake、unstake和领取奖励都检查这个池子是否暂停
+///
+/// 用户stake `staking_mint`, 每个slot从vault发放`reward_rate`的`reward_mint`, 按stake数额分给所有用户
+pub fn initialize_staking(ctx: Context<InitializeStaking>, reward_rate: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.staking_pool.set_inner(StakingPool::new(
+        ctx.accounts.signer.key(),
+        (
+            ctx.accounts.staking_mint.key(),
+            ctx.accounts.reward_mint.key(),
+        ),
+        (
+            ctx.accounts.token_vault_account.key(),
+            ctx.accounts.pool.key(),
+        ),
+        reward_rate,
+        clock.slot,
+        ctx.bumps.staking_pool,
+    ));
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(reward_rate: u64)]
 pub struct InitializeStaking<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
+    // 本程序, 用于找到它的ProgramData账户
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, PtSolProgram>,
+
+    // 本程序的ProgramData, 记录了升级权限
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(signer.key())
+            @ StakingError::InvalidStakingAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    // 全局的stake配置
+    #[account(
+        init,
+        seeds = [StakingPool::SEED_PREFIX.as_bytes()],
+        bump,
+        payer = signer,
+        space = StakingPool::SPACE,
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
     // stake生成token奖励的账户
     #[account(
         init_if_needed,
         seeds = [VAULT_SEED],
         bump,
         payer = signer,
-        token::mint = mint,
+        token::mint = reward_mint,
         token::authority = token_vault_account,
+        token::token_program = token_program,
+    )]
+    pub token_vault_account: InterfaceAccount<'info, TokenAccount>,
+
+    // stake对应的流动性池子
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
     )]
-    pub token_vault_account: Account<'info, TokenAccount>,
+    pub pool: Account<'info, LiquidityPool>,
 
-    pub mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
+    // stake的铸币
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    // 奖励的铸币
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    // 奖励的Token程序
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
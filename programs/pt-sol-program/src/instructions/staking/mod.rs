@@ -1,7 +1,11 @@
+pub mod claim_rewards;
 pub mod initialize_staking;
+pub mod set_reward_rate;
 pub mod stake;
 pub mod unstake;
 
+pub use claim_rewards::*;
 pub use initialize_staking::*;
+pub use set_reward_rate::*;
 pub use stake::*;
 pub use unstake::*;
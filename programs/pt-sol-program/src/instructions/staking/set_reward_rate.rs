@@ -0,0 +1,28 @@
+use crate::{error::*, state::StakingPool};
+use anchor_lang::prelude::*;
+
+/// 调整每个slot发放的奖励, 只有`StakingPool`的管理员可以调用
+///
+/// 之前的slot按原来的速率结算到累计奖励中, 新的速率只影响之后的slot
+pub fn set_reward_rate(ctx: Context<SetRewardRate>, reward_rate: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let staking_pool = &mut ctx.accounts.staking_pool;
+    staking_pool.update(clock.slot)?;
+    staking_pool.reward_rate = reward_rate;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    // 全局的stake配置
+    #[account(
+        mut,
+        seeds = [StakingPool::SEED_PREFIX.as_bytes()],
+        bump = staking_pool.bump,
+        has_one = authority @ StakingError::InvalidStakingAuthority,
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // StakingPool的管理员
+    pub authority: Signer<'info>,
+}
@@ -1,13 +1,15 @@
 use crate::{
     constants::{STAKE_INFO_SEED, THREAD_AUTHORITY_SEED, TOKEN_SEED},
     error::*,
+    events::StakeEvent,
     state::*,
     ID,
 };
 use anchor_lang::{prelude::*, InstructionData};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, transfer, Mint, Token, TokenAccount, Transfer},
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use clockwork_sdk::{
     state::{Thread, Trigger},
@@ -15,22 +17,17 @@ use clockwork_sdk::{
 };
 use solana_program::{clock::Clock, instruction::Instruction, native_token::LAMPORTS_PER_SOL};
 
-pub fn stake(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>) -> Result<()> {
-    // 先拿到stake信息
-    let stake_info = &mut ctx.accounts.stake_info_account;
-    // let thread_id = StakeInfo::get_thread_id(stake_info.key());
-    if stake_info.is_staked {
-        return Err(StakingError::IsStaked.into());
-    }
-
+/// stake `amount`个token, 可以多次调用追加stake, 追加前先结算之前的奖励
+///
+/// `lockup`为`StakingPool::LOCKUPS`中锁仓选项的编号, 锁仓期内不能unstake, 奖励按倍数加权
+pub fn stake(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>, lockup: u8) -> Result<()> {
     if amount <= 0 {
         return Err(StakingError::NoTokens.into());
     }
 
-    let clock = Clock::get()?;
+    let (lock_slots, multiplier_bps) = StakingPool::lockup(lockup)?;
 
-    stake_info.stake_at_slot = clock.slot;
-    stake_info.is_staked = true;
+    let clock = Clock::get()?;
 
     // stake计算
     let stake_amount = (amount)
@@ -38,19 +35,52 @@ pub fn stake(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>) -> Result<()>
         .unwrap();
 
     // user的token账户 => stake账户
-    transfer(
+    transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.user_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.stake_account.to_account_info(),
                 authority: ctx.accounts.signer.to_account_info(),
             },
         ),
         stake_amount,
+        ctx.accounts.mint.decimals,
     )?;
 
-    schedule_auto_fund_pool(ctx, amount, thread_id)?;
+    // 先按之前的权重结算奖励, 再加入新的stake和锁仓重新计算权重
+    let stake_info = &mut ctx.accounts.stake_info_account;
+    let staking_pool = &mut ctx.accounts.staking_pool;
+    staking_pool.update(clock.slot)?;
+    staking_pool.settle(stake_info, clock.slot)?;
+    staking_pool.remove_weight(stake_info)?;
+    stake_info.amount = stake_info
+        .amount
+        .checked_add(stake_amount)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let lock_end_slot = StakingPool::lock_end_slot(clock.slot, lock_slots)?;
+    stake_info.lock(lock_end_slot, multiplier_bps, clock.slot)?;
+    staking_pool.add_weight(stake_info)?;
+    staking_pool.total_staked = staking_pool
+        .total_staked
+        .checked_add(stake_amount)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    stake_info.stake_at_slot = clock.slot;
+
+    emit!(StakeEvent {
+        user: ctx.accounts.signer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount: stake_amount,
+        slot: clock.slot,
+        lock_end_slot: stake_info.lock_end_slot,
+        multiplier_bps: stake_info.multiplier_bps,
+    });
+
+    // 追加stake时Thread已经存在
+    if ctx.accounts.thread.data_is_empty() {
+        schedule_auto_fund_pool(ctx, amount, thread_id)?;
+    }
 
     Ok(())
 }
@@ -78,15 +108,20 @@ fn schedule_auto_fund_pool(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>)
             system_program: system_program.key(),
             payer: payer.key(),
             payer_token_account: ctx.accounts.stake_account.key(),
-            thread: thread.key(),
+            lp_mint: pool.lp_mint,
+            payer_lp_token_account: get_associated_token_address(&payer.key(), &pool.lp_mint),
+            pool_lp_token_account: get_associated_token_address(&pool.key(), &pool.lp_mint),
+            thread: Some(thread.key()),
             thread_authority: thread_authority.key(),
             mint: mint.key(),
             token_program: token_program.key(),
+            lp_token_program: token::ID,
             associated_token_program: associated_token_program.key(),
         }
         .to_account_metas(Some(true)),
         data: crate::instruction::FundPool {
             amount: pool_token_account.amount.checked_div(100).unwrap(),
+            list_asset: false,
         }
         .data(),
     };
@@ -121,7 +156,7 @@ fn schedule_auto_fund_pool(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>)
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64, thread_id: Vec<u8>)]
+#[instruction(amount: u64, thread_id: Vec<u8>, lockup: u8)]
 pub struct Stake<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -136,6 +171,14 @@ pub struct Stake<'info> {
     )]
     pub stake_info_account: Account<'info, StakeInfo>,
 
+    // 全局的stake配置
+    #[account(
+        mut,
+        seeds = [StakingPool::SEED_PREFIX.as_bytes()],
+        bump = staking_pool.bump,
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
     // user对应的stake账户
     #[account(
         init_if_needed,
@@ -144,21 +187,25 @@ pub struct Stake<'info> {
         payer = signer,
         token::mint = mint,
         token::authority = stake_account,
+        token::token_program = token_program,
     )]
-    pub stake_account: Account<'info, TokenAccount>,
+    pub stake_account: InterfaceAccount<'info, TokenAccount>,
 
     // user的token账户
     #[account(
         mut,
         associated_token::mint = mint,
         associated_token::authority = signer,
+        associated_token::token_program = token_program,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [LiquidityPool::SEED_PREFIX.as_bytes()],
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
         bump = pool.bump,
+        address = staking_pool.pool @ StakingError::InvalidPool,
+        constraint = pool.is_active() @ SwapProgramError::PoolPaused,
     )]
     pub pool: Account<'info, LiquidityPool>,
 
@@ -167,23 +214,26 @@ pub struct Stake<'info> {
         payer = signer,
         associated_token::mint = mint,
         associated_token::authority = pool,
+        associated_token::token_program = token_program,
     )]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub mint: Account<'info, Mint>,
+    #[account(address = staking_pool.staking_mint @ StakingError::InvalidStakingMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
     // solana ecosystem program
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 
     // clockwork
     /// Address to assign to the newly created thread.
+    /// CHECK: 第一次stake时还没有创建, 追加stake时为已经存在的Thread
     #[account(
         mut,
         address = Thread::pubkey(thread_authority.key(), thread_id),
     )]
-    pub thread: SystemAccount<'info>,
+    pub thread: UncheckedAccount<'info>,
 
     /// The pda that will own and manage the thread.
     #[account(seeds = [THREAD_AUTHORITY_SEED], bump)]
@@ -1,73 +1,111 @@
 use crate::{
     constants::{STAKE_INFO_SEED, THREAD_AUTHORITY_SEED, TOKEN_SEED, VAULT_SEED},
     error::*,
-    state::StakeInfo,
+    events::UnstakeEvent,
+    state::{LiquidityPool, StakeInfo, StakingPool},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, transfer, Mint, Token, TokenAccount, Transfer},
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use clockwork_sdk::{cpi::ThreadDelete, state::{Thread, ThreadAccount}, ThreadProgram};
 use solana_program::clock::Clock;
 
-pub fn unstake(ctx: Context<UnStake>) -> Result<()> {
+/// unstake `amount`个token(与`stake`的单位相同), 同时转出已经结算的奖励
+///
+/// 奖励最多转出vault中现有的余额, 其余的保留在`reward_owed`中, 之后可以用`claim_rewards`领取,
+/// vault余额不足时不会影响取回stake。锁仓期内不能unstake, 全部unstake之后销毁Thread
+pub fn unstake(ctx: Context<UnStake>, amount: u64) -> Result<()> {
     // 先拿到stake信息
     let stake_info = &mut ctx.accounts.stake_info_account;
 
-    if !stake_info.is_staked {
+    if stake_info.amount == 0 {
         return Err(StakingError::NotStaked.into());
     }
 
-    let clock = Clock::get()?;
-    let slots_passed = clock.slot - stake_info.stake_at_slot;
+    if amount == 0 {
+        return Err(StakingError::NoTokens.into());
+    }
 
-    let stake_amount = ctx.accounts.stake_account.amount;
+    let clock = Clock::get()?;
+    if stake_info.is_locked(clock.slot) {
+        return Err(StakingError::StakeLocked.into());
+    }
 
-    // reward计算
-    let reward = (slots_passed as u64)
+    let stake_amount = amount
         .checked_mul(10u64.pow(ctx.accounts.mint.decimals as u32))
-        .unwrap();
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let staked = stake_info
+        .amount
+        .checked_sub(stake_amount)
+        .ok_or(StakingError::InsufficientStake)?;
+
+    // reward计算: 结算按之前的权重分得的累计奖励, 再减少stake
+    let staking_pool = &mut ctx.accounts.staking_pool;
+    staking_pool.update(clock.slot)?;
+    staking_pool.settle(stake_info, clock.slot)?;
+    staking_pool.remove_weight(stake_info)?;
+    stake_info.amount = staked;
+    staking_pool.add_weight(stake_info)?;
+    staking_pool.total_staked = staking_pool.total_staked.saturating_sub(stake_amount);
+    let reward = stake_info.reward_owed.min(ctx.accounts.token_vault_account.amount);
+    stake_info.reward_owed -= reward;
 
     let bump = ctx.bumps.token_vault_account;
 
     let signer: &[&[&[u8]]] = &[&[VAULT_SEED, &[bump]]];
 
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.token_vault_account.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: ctx.accounts.token_vault_account.to_account_info(),
-            },
-            signer,
-        ),
-        reward,
-    )?;
+    if reward > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.reward_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_vault_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.user_reward_token_account.to_account_info(),
+                    authority: ctx.accounts.token_vault_account.to_account_info(),
+                },
+                signer,
+            ),
+            reward,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+    }
 
     let staker = ctx.accounts.signer.key();
     let bump = ctx.bumps.stake_account;
     let signer: &[&[&[u8]]] = &[&[TOKEN_SEED, staker.as_ref(), &[bump]]];
 
-    transfer(
+    transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.stake_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.user_token_account.to_account_info(),
                 authority: ctx.accounts.stake_account.to_account_info(),
             },
             signer,
         ),
         stake_amount,
+        ctx.accounts.mint.decimals,
     )?;
 
     // reset stakeInfo
-    stake_info.is_staked = false;
     stake_info.stake_at_slot = clock.slot;
 
-    cleanup(&ctx)?;
+    emit!(UnstakeEvent {
+        user: staker,
+        mint: ctx.accounts.mint.key(),
+        amount: stake_amount,
+        reward,
+        slot: clock.slot,
+    });
+
+    if staked == 0 {
+        cleanup(&ctx)?;
+    }
 
     Ok(())
 }
@@ -81,7 +119,7 @@ fn cleanup(ctx: &Context<UnStake>) -> Result<()> {
             close_to: ctx.accounts.signer.to_account_info(),
             thread: ctx.accounts.thread.to_account_info(),
         },
-        &[&[ctx.accounts.mint.key().as_ref(), &[bump]]],
+        &[&[THREAD_AUTHORITY_SEED, &[bump]]],
     ))?;
 
     Ok(())
@@ -97,8 +135,17 @@ pub struct UnStake<'info> {
         mut,
         seeds = [VAULT_SEED],
         bump,
+        address = staking_pool.vault @ StakingError::InvalidVault,
     )]
-    pub token_vault_account: Account<'info, TokenAccount>,
+    pub token_vault_account: InterfaceAccount<'info, TokenAccount>,
+
+    // 全局的stake配置
+    #[account(
+        mut,
+        seeds = [StakingPool::SEED_PREFIX.as_bytes()],
+        bump = staking_pool.bump,
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
 
     // stake_info账户，存放stake的信息(不是Token账户，这里是自定义的)
     #[account(
@@ -114,18 +161,45 @@ pub struct UnStake<'info> {
         seeds = [TOKEN_SEED, signer.key.as_ref()],
         bump,
     )]
-    pub stake_account: Account<'info, TokenAccount>,
+    pub stake_account: InterfaceAccount<'info, TokenAccount>,
 
     // user的token账户
     #[account(
         mut,
         associated_token::mint = mint,
         associated_token::authority = signer,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // stake时对应的流动性池子, 暂停时(非withdraw-only模式)不能unstake
+    #[account(
+        seeds = [LiquidityPool::SEED_PREFIX.as_bytes(), pool.pool_id.to_le_bytes().as_ref()],
+        bump = pool.bump,
+        address = staking_pool.pool @ StakingError::InvalidPool,
+        constraint = pool.allows_withdrawals() @ SwapProgramError::PoolPaused,
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(address = staking_pool.staking_mint @ StakingError::InvalidStakingMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // 奖励的铸币
+    #[account(address = staking_pool.reward_mint @ StakingError::InvalidRewardMint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    // user接收奖励的token账户
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = reward_token_program,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_reward_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 
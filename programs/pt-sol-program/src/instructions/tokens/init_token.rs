@@ -1,5 +1,5 @@
 use {
-    crate::constants::METADATA_SEED,
+    crate::{constants::METADATA_SEED, events::InitTokenEvent},
     anchor_lang::prelude::*,
     anchor_spl::{
         metadata::{
@@ -12,9 +12,9 @@ use {
 
 pub fn init_token(ctx: Context<InitToken>, metadata: InitTokenParams) -> Result<()> {
     let token_data: DataV2 = DataV2 {
-        name: metadata.name,
-        symbol: metadata.symbol,
-        uri: metadata.uri,
+        name: metadata.name.clone(),
+        symbol: metadata.symbol.clone(),
+        uri: metadata.uri.clone(),
         seller_fee_basis_points: 0,
         creators: None,
         collection: None,
@@ -40,6 +40,15 @@ pub fn init_token(ctx: Context<InitToken>, metadata: InitTokenParams) -> Result<
         None,
     )?;
 
+    emit!(InitTokenEvent {
+        mint: ctx.accounts.mint.key(),
+        authority: ctx.accounts.payer.key(),
+        name: metadata.name,
+        symbol: metadata.symbol,
+        uri: metadata.uri,
+        decimals: ctx.accounts.mint.decimals,
+    });
+
     msg!("Token mint created successfully");
     Ok(())
 }
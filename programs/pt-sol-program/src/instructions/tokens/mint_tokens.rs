@@ -1,4 +1,5 @@
 use {
+    crate::events::MintTokensEvent,
     anchor_lang::prelude::*,
     anchor_spl::{
         associated_token::AssociatedToken,
@@ -7,6 +8,8 @@ use {
 };
 
 pub fn mint_tokens(ctx: Context<MintTokens>, quantity: u64) -> Result<()> {
+    let amount = quantity * 10u64.pow(ctx.accounts.mint.decimals as u32);
+
     // Invoke the mint_to instruction on the token program
     mint_to(
         CpiContext::new(
@@ -17,9 +20,15 @@ pub fn mint_tokens(ctx: Context<MintTokens>, quantity: u64) -> Result<()> {
                 authority: ctx.accounts.mint_authority.to_account_info(),
             },
         ),
-        quantity * 10u64.pow(ctx.accounts.mint.decimals as u32), // Mint tokens, adjust for decimals
+        amount, // Mint tokens, adjust for decimals
     )?;
 
+    emit!(MintTokensEvent {
+        mint: ctx.accounts.mint.key(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+    });
+
     msg!("Token minted successfully.");
 
     Ok(())
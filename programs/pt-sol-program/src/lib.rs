@@ -4,9 +4,12 @@ use anchor_lang::prelude::*;
 // 2. Declare Program ID (SolPG will automatically update this when you deploy)
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod math;
 pub mod state;
 use instructions::*;
+use state::CurveType;
 
 declare_id!("iYKtp9m8Kf922xuDmNjLmJ1AQQYRCNJE99AHfY4NYRJ");
 
@@ -23,32 +26,248 @@ pub mod pt_sol_program {
         tokens::mint_tokens(ctx, quantity)
     }
 
-    /// Initialize the program by creating the liquidity pool
-    pub fn create_pool(ctx: Context<CreatePool>) -> Result<()> {
-        liq_pool::create_pool(ctx)
+    /// Create a new liquidity pool, keyed by `pool_id`, with the chosen curve
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        pool_id: u64,
+        fee_bps: u16,
+        curve: CurveType,
+        amp: u64,
+    ) -> Result<()> {
+        liq_pool::create_pool(ctx, pool_id, fee_bps, curve, amp)
+    }
+
+    /// Update the swap fee of a pool (pool authority only)
+    pub fn set_pool_fee(ctx: Context<SetPoolFee>, fee_bps: u16) -> Result<()> {
+        liq_pool::set_pool_fee(ctx, fee_bps)
+    }
+
+    /// Ramp the weight of every asset in the pool to new targets (pool authority only)
+    pub fn set_asset_weights(
+        ctx: Context<SetAssetWeights>,
+        weights: Vec<u64>,
+        ramp_end_slot: u64,
+    ) -> Result<()> {
+        liq_pool::set_asset_weights(ctx, weights, ramp_end_slot)
+    }
+
+    /// Ramp the amplification coefficient of a StableSwap pool (pool authority only)
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, ramp_end_slot: u64) -> Result<()> {
+        liq_pool::ramp_amp(ctx, target_amp, ramp_end_slot)
+    }
+
+    /// Set the protocol share of the swap fee and its treasury (pool authority only)
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        protocol_fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        liq_pool::set_protocol_fee(ctx, protocol_fee_bps, treasury)
+    }
+
+    /// Propose a new authority for a pool (pool authority only)
+    pub fn propose_pool_authority(
+        ctx: Context<ProposePoolAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        liq_pool::propose_pool_authority(ctx, new_authority)
+    }
+
+    /// Accept the authority of a pool (proposed authority only)
+    pub fn accept_pool_authority(ctx: Context<AcceptPoolAuthority>) -> Result<()> {
+        liq_pool::accept_pool_authority(ctx)
+    }
+
+    /// Remove an asset with an empty pool token account from a pool (pool authority only)
+    pub fn delist_asset(ctx: Context<DelistAsset>) -> Result<()> {
+        liq_pool::delist_asset(ctx)
+    }
+
+    /// Halt a pool, optionally still allowing withdrawals (pool authority only)
+    pub fn pause(ctx: Context<UpdatePoolStatus>, withdraw_only: bool) -> Result<()> {
+        liq_pool::pause(ctx, withdraw_only)
+    }
+
+    /// Resume a paused pool (pool authority only)
+    pub fn unpause(ctx: Context<UpdatePoolStatus>) -> Result<()> {
+        liq_pool::unpause(ctx)
+    }
+
+    /// Withdraw the protocol fees accrued for one asset (pool treasury only)
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        liq_pool::collect_protocol_fees(ctx)
     }
 
     /// Provide liquidity to the pool by funding it with some asset
-    pub fn fund_pool(ctx: Context<FundPool>, amount: u64) -> Result<()> {
-        liq_pool::fund_pool(ctx, amount)
+    pub fn fund_pool(ctx: Context<FundPool>, amount: u64, list_asset: bool) -> Result<()> {
+        liq_pool::fund_pool(ctx, amount, list_asset)
+    }
+
+    /// Burn LP shares and withdraw a pro-rata slice of every asset in the pool
+    pub fn withdraw_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawLiquidity<'info>>,
+        shares: u64,
+    ) -> Result<()> {
+        liq_pool::withdraw_liquidity(ctx, shares)
+    }
+
+    /// Swap交易, 收到的数额低于`min_amount_out`时交易失败
+    pub fn swap(ctx: Context<Swap>, amount_to_swap: u64, min_amount_out: u64) -> Result<()> {
+        liq_pool::swap(ctx, amount_to_swap, min_amount_out)
+    }
+
+    /// Swap交易(指定接收数额), 需要支付的数额高于`max_amount_in`时交易失败
+    pub fn swap_exact_out(ctx: Context<Swap>, amount_out: u64, max_amount_in: u64) -> Result<()> {
+        liq_pool::swap_exact_out(ctx, amount_out, max_amount_in)
+    }
+
+    /// 在同一个池子中沿着`remaining_accounts`给出的路径连续swap, 收到的数额低于`min_amount_out`时交易失败
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        liq_pool::swap_route(ctx, amount_in, min_amount_out)
+    }
+
+    /// 只读的swap报价, 通过return data返回收到的数额、手续费和价格影响
+    pub fn quote_swap(ctx: Context<QuoteSwap>, pay_amount: u64) -> Result<SwapQuote> {
+        liq_pool::quote_swap(ctx, pay_amount)
+    }
+
+    /// 闪电贷: 借出`amount`并CPI调用`borrower_program`, 返回之前必须还款并支付手续费
+    pub fn flash_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+        amount: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        liq_pool::flash_loan(ctx, amount, data)
+    }
+
+    /// 为池子中的一对资产创建TWAP预言机
+    pub fn initialize_oracle(ctx: Context<InitializeOracle>) -> Result<()> {
+        liq_pool::initialize_oracle(ctx)
+    }
+
+    /// 把池子当前的累计价格记录到预言机中
+    pub fn update_oracle(ctx: Context<UpdateOracle>) -> Result<()> {
+        liq_pool::update_oracle(ctx)
+    }
+
+    /// 返回最近`window_slots`个slot内的时间加权平均价格(WAD)
+    pub fn get_twap(ctx: Context<GetTwap>, window_slots: u64) -> Result<u128> {
+        liq_pool::get_twap(ctx, window_slots)
+    }
+
+    /// 创建集中流动性池子, 初始价格为`sqrt_price_x64`(Q64.64格式的价格平方根)
+    pub fn create_concentrated_pool(
+        ctx: Context<CreateConcentratedPool>,
+        pool_id: u64,
+        tick_spacing: u16,
+        fee_bps: u16,
+        sqrt_price_x64: u128,
+    ) -> Result<()> {
+        concentrated::create_concentrated_pool(ctx, pool_id, tick_spacing, fee_bps, sqrt_price_x64)
+    }
+
+    pub fn initialize_tick_array(
+        ctx: Context<InitializeTickArray>,
+        start_tick_index: i32,
+    ) -> Result<()> {
+        concentrated::initialize_tick_array(ctx, start_tick_index)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        concentrated::open_position(ctx, tick_lower, tick_upper)
+    }
+
+    pub fn increase_liquidity(
+        ctx: Context<ModifyLiquidity>,
+        liquidity: u128,
+        amount_a_max: u64,
+        amount_b_max: u64,
+    ) -> Result<()> {
+        concentrated::increase_liquidity(ctx, liquidity, amount_a_max, amount_b_max)
+    }
+
+    pub fn decrease_liquidity(
+        ctx: Context<ModifyLiquidity>,
+        liquidity: u128,
+        amount_a_min: u64,
+        amount_b_min: u64,
+    ) -> Result<()> {
+        concentrated::decrease_liquidity(ctx, liquidity, amount_a_min, amount_b_min)
+    }
+
+    pub fn collect_fees(ctx: Context<ModifyLiquidity>) -> Result<()> {
+        concentrated::collect_fees(ctx)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        concentrated::close_position(ctx)
+    }
+
+    /// 集中流动性池子的Swap交易, 经过的`TickArray`通过`remaining_accounts`传入
+    pub fn swap_concentrated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapConcentrated<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        a_to_b: bool,
+    ) -> Result<()> {
+        concentrated::swap_concentrated(ctx, amount_in, min_amount_out, a_to_b)
+    }
+
+    /// 紧急暂停集中流动性池子(仅管理员), `withdraw_only`时仍然允许取回流动性和领取手续费
+    pub fn pause_concentrated(
+        ctx: Context<UpdateConcentratedPoolStatus>,
+        withdraw_only: bool,
+    ) -> Result<()> {
+        concentrated::pause_concentrated(ctx, withdraw_only)
+    }
+
+    /// 恢复暂停的集中流动性池子(仅管理员)
+    pub fn unpause_concentrated(ctx: Context<UpdateConcentratedPoolStatus>) -> Result<()> {
+        concentrated::unpause_concentrated(ctx)
+    }
+
+    /// 设置集中流动性池子的协议手续费和treasury(仅管理员)
+    pub fn set_concentrated_protocol_fee(
+        ctx: Context<SetConcentratedProtocolFee>,
+        protocol_fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        concentrated::set_concentrated_protocol_fee(ctx, protocol_fee_bps, treasury)
+    }
+
+    /// 领取集中流动性池子累计的协议手续费(仅treasury)
+    pub fn collect_concentrated_protocol_fees(
+        ctx: Context<CollectConcentratedProtocolFees>,
+    ) -> Result<()> {
+        concentrated::collect_concentrated_protocol_fees(ctx)
+    }
+
+    /// 初始化stake, 创建全局的`StakingPool`, `reward_rate`为每个slot发放的奖励
+    pub fn initialize_staking(ctx: Context<InitializeStaking>, reward_rate: u64) -> Result<()> {
+        staking::initialize_staking(ctx, reward_rate)
     }
 
-    /// Swap交易
-    pub fn swap(ctx: Context<Swap>, amount_to_swap: u64) -> Result<()> {
-        liq_pool::swap(ctx, amount_to_swap)
+    /// 调整stake每个slot发放的奖励, 只有管理员可以调用
+    pub fn set_reward_rate(ctx: Context<SetRewardRate>, reward_rate: u64) -> Result<()> {
+        staking::set_reward_rate(ctx, reward_rate)
     }
 
-    pub fn initialize_staking(ctx: Context<InitializeStaking>) -> Result<()> {
-        staking::initialize_staking(ctx)
+    /// stake, 可以多次调用追加stake, `lockup`为锁仓选项的编号
+    pub fn stake(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>, lockup: u8) -> Result<()> {
+        staking::stake(ctx, amount, thread_id, lockup)
     }
 
-    /// stake
-    pub fn stake(ctx: Context<Stake>, amount: u64, thread_id: Vec<u8>) -> Result<()> {
-        staking::stake(ctx, amount, thread_id)
+    /// unstake, 可以只取回部分stake
+    pub fn unstake(ctx: Context<UnStake>, amount: u64) -> Result<()> {
+        staking::unstake(ctx, amount)
     }
 
-    /// unstake
-    pub fn unstake(ctx: Context<UnStake>) -> Result<()> {
-        staking::unstake(ctx)
+    /// 领取stake累计的奖励, 不取回stake
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        staking::claim_rewards(ctx)
     }
 }
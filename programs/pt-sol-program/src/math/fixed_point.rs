@@ -0,0 +1,259 @@
+//! 18-decimal ("WAD") fixed-point `ln`, `exp` and `pow`
+//!
+//! Used wherever the pool needs a fractional power, e.g. the weight ratio
+//! exponent of a weighted swap, or the root of the balance growth when pricing
+//! a single-asset deposit in LP shares.
+
+use super::mul_div_floor;
+
+/// `1.0` in WAD
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+const WAD_I: i128 = WAD as i128;
+
+/// `ln(2)` in WAD
+const LN_2: i128 = 693_147_180_559_945_309;
+
+/// Upper bound of the relative error of `pow`, in WAD (`1e-14`). Callers use
+/// it to round results in the pool's favour
+pub const MAX_POW_RELATIVE_ERROR: u128 = 10_000;
+
+/// Natural logarithm of a WAD value, returned as a signed WAD value
+pub fn ln(x: u128) -> Option<i128> {
+    if x == 0 {
+        return None;
+    }
+
+    // x = y * 2^n, y in [1, 2)
+    let mut n: i128 = 0;
+    let mut y = x;
+    while y >= 2 * WAD {
+        y >>= 1;
+        n += 1;
+    }
+    while y < WAD {
+        y <<= 1;
+        n -= 1;
+    }
+
+    // ln(y) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...), z = (y - 1) / (y + 1) <= 1/3
+    let z = ((y - WAD) * WAD / (y + WAD)) as i128;
+    let z_squared = z * z / WAD_I;
+    let mut term = z;
+    let mut sum = z;
+    let mut i = 3;
+    while term != 0 {
+        term = term * z_squared / WAD_I;
+        sum += term / i;
+        i += 2;
+    }
+
+    Some(n * LN_2 + 2 * sum)
+}
+
+/// `e^x` of a signed WAD value, returned as a WAD value
+pub fn exp(x: i128) -> Option<u128> {
+    // x = k * ln(2) + r, r in [0, ln(2))
+    let k = x.div_euclid(LN_2);
+    let r = x.rem_euclid(LN_2);
+
+    // e^r = 1 + r + r^2/2! + r^3/3! + ...
+    let mut term = WAD_I;
+    let mut sum = WAD_I;
+    let mut i = 1;
+    while term != 0 {
+        term = term * r / WAD_I / i;
+        sum += term;
+        i += 1;
+    }
+
+    // e^x = e^r * 2^k
+    let sum = sum as u128;
+    if k >= 0 {
+        if k >= sum.leading_zeros() as i128 {
+            return None;
+        }
+        Some(sum << k)
+    } else if k > -128 {
+        Some(sum >> -k)
+    } else {
+        Some(0)
+    }
+}
+
+/// `base^exponent` of two WAD values
+pub fn pow(base: u128, exponent: u128) -> Option<u128> {
+    if exponent == 0 {
+        return Some(WAD);
+    }
+    if base == 0 {
+        return Some(0);
+    }
+
+    // exponent * ln(base), kept in 256 bits until the division by WAD
+    let ln_base = ln(base)?;
+    let product = mul_div_floor(ln_base.unsigned_abs(), exponent, WAD)?;
+    let product = i128::try_from(product).ok()?;
+    exp(if ln_base < 0 { -product } else { product })
+}
+
+/// `base^exponent`, reduced by `MAX_POW_RELATIVE_ERROR` so it never exceeds the
+/// exact value
+pub fn pow_down(base: u128, exponent: u128) -> Option<u128> {
+    let result = pow(base, exponent)?;
+    let error = result
+        .checked_mul(MAX_POW_RELATIVE_ERROR)?
+        .checked_div(WAD)?
+        .checked_add(1)?;
+    Some(result.saturating_sub(error))
+}
+
+/// `base^exponent`, increased by `MAX_POW_RELATIVE_ERROR` so it is never less
+/// than the exact value
+pub fn pow_up(base: u128, exponent: u128) -> Option<u128> {
+    let result = pow(base, exponent)?;
+    let error = result
+        .checked_mul(MAX_POW_RELATIVE_ERROR)?
+        .checked_div(WAD)?
+        .checked_add(1)?;
+    result.checked_add(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // |actual - expected| <= expected * MAX_POW_RELATIVE_ERROR / WAD + 1
+    fn assert_close(actual: u128, expected: u128) {
+        let tolerance = expected * MAX_POW_RELATIVE_ERROR / WAD + 1;
+        assert!(
+            actual.abs_diff(expected) <= tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn ln_rejects_zero() {
+        assert_eq!(ln(0), None);
+    }
+
+    #[test]
+    fn ln_of_powers_of_two_is_exact() {
+        assert_eq!(ln(WAD), Some(0));
+        assert_eq!(ln(2 * WAD), Some(LN_2));
+        assert_eq!(ln(WAD / 2), Some(-LN_2));
+        assert_eq!(ln(1024 * WAD), Some(10 * LN_2));
+    }
+
+    #[test]
+    fn ln_handles_the_whole_u128_range() {
+        // ln(1e-18) = -41.446531673892822312
+        let smallest = ln(1).unwrap();
+        assert!(smallest.abs_diff(-41_446_531_673_892_822_312) <= 100);
+        // ln(u128::MAX / 1e18) = 47.197...
+        let largest = ln(u128::MAX).unwrap();
+        assert!(largest > 47 * WAD_I && largest < 48 * WAD_I);
+    }
+
+    #[test]
+    fn exp_of_zero_is_one() {
+        assert_eq!(exp(0), Some(WAD));
+    }
+
+    #[test]
+    fn exp_overflows_to_none_and_underflows_to_zero() {
+        // e^89 > u128::MAX / 1e18
+        assert_eq!(exp(89 * WAD_I), None);
+        assert!(exp(40 * WAD_I).is_some());
+        assert_eq!(exp(-100 * WAD_I), Some(0));
+        assert_eq!(exp(i128::MIN / 2), Some(0));
+    }
+
+    #[test]
+    fn pow_edge_cases() {
+        assert_eq!(pow(0, 0), Some(WAD));
+        assert_eq!(pow(5 * WAD, 0), Some(WAD));
+        assert_eq!(pow(0, WAD / 2), Some(0));
+        assert_close(pow(WAD, 3 * WAD).unwrap(), WAD);
+        assert_close(pow(7 * WAD, WAD).unwrap(), 7 * WAD);
+    }
+
+    #[test]
+    fn pow_down_and_pow_up_bracket_pow() {
+        let cases = [
+            (2 * WAD, WAD / 2),
+            (WAD / 3, 3 * WAD),
+            (1_000_001 * WAD / 1_000_000, 4 * WAD),
+            (WAD + 1, WAD),
+            (123_456 * WAD, WAD / 7),
+        ];
+        for (base, exponent) in cases {
+            let exact = pow(base, exponent).unwrap();
+            let down = pow_down(base, exponent).unwrap();
+            let up = pow_up(base, exponent).unwrap();
+            assert!(down < exact && exact < up);
+            assert_close(down, exact);
+            assert_close(up, exact);
+        }
+    }
+
+    #[test]
+    fn pow_down_never_underflows() {
+        assert_eq!(pow_down(0, WAD), Some(0));
+        assert_eq!(pow_down(1, 2 * WAD), Some(0));
+    }
+
+    // (x, ln(x)), the expected values computed to 60 significant digits
+    const LN_TABLE: [(u128, i128); 5] = [
+        (3 * WAD, 1_098_612_288_668_109_691),
+        (10 * WAD, 2_302_585_092_994_045_684),
+        (WAD / 10, -2_302_585_092_994_045_685),
+        (123_456_789 * WAD / 1_000, 11_723_646_487_185_880_981),
+        (WAD + WAD / 1_000_000, 999_999_500_000),
+    ];
+
+    // (x, exp(x)), truncated
+    const EXP_TABLE: [(i128, u128); 4] = [
+        (WAD_I, 2_718_281_828_459_045_235),
+        (-WAD_I, 367_879_441_171_442_321),
+        (5 * WAD_I / 2, 12_182_493_960_703_473_438),
+        (-10 * WAD_I, 45_399_929_762_484),
+    ];
+
+    // (base, exponent, base^exponent), truncated
+    const POW_TABLE: [(u128, u128, u128); 5] = [
+        (2 * WAD, WAD / 2, 1_414_213_562_373_095_048),
+        (3 * WAD / 2, 5 * WAD / 2, 2_755_675_960_631_075_360),
+        (4 * WAD / 5, WAD / 4, 945_741_609_003_175_813),
+        (10 * WAD, 3 * WAD, 1_000 * WAD),
+        (WAD / 2, 42 * WAD / 10, 54_409_410_206_007_758),
+    ];
+
+    #[test]
+    fn ln_matches_known_values() {
+        for (x, expected) in LN_TABLE {
+            let actual = ln(x).unwrap();
+            assert!(actual.abs_diff(expected) <= 100, "ln({}) = {}", x, actual);
+        }
+    }
+
+    #[test]
+    fn exp_matches_known_values() {
+        for (x, expected) in EXP_TABLE {
+            assert_close(exp(x).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn pow_matches_known_values_within_the_error_bound() {
+        for (base, exponent, expected) in POW_TABLE {
+            assert_close(pow(base, exponent).unwrap(), expected);
+            // the bounds enclose the exact value
+            assert!(pow_down(base, exponent).unwrap() <= expected);
+            assert!(pow_up(base, exponent).unwrap() >= expected);
+        }
+    }
+}
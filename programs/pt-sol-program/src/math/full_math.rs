@@ -0,0 +1,153 @@
+//! Full-precision multiply-then-divide on `u128` values
+//!
+//! Pool balances are `u64`, so the products that show up in the swap formulas
+//! (e.g. `R * p * γ`) can exceed `u128`. These helpers keep the intermediate
+//! product in 256 bits so the result is exact and only the final quotient has
+//! to fit in a `u128`.
+
+/// Multiplies two `u128` values into a 256-bit `(hi, lo)` pair
+pub fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a0, a1) = (a & MASK, a >> 64);
+    let (b0, b1) = (b & MASK, b >> 64);
+
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+
+    // 中间64位的进位: 最多为 3 * 2^64, 不会溢出
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+
+    let lo = (p00 & MASK) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+
+    (hi, lo)
+}
+
+/// Computes `a * b / denominator` and its remainder, returning `None` if the
+/// denominator is zero or the quotient does not fit in a `u128`
+pub fn mul_div_rem(a: u128, b: u128, denominator: u128) -> Option<(u128, u128)> {
+    if denominator == 0 {
+        return None;
+    }
+
+    let (hi, lo) = full_mul(a, b);
+    if hi == 0 {
+        return Some((lo / denominator, lo % denominator));
+    }
+    if hi >= denominator {
+        return None;
+    }
+
+    // 256位除以128位的长除法, 余数始终小于 `denominator`
+    let mut remainder = hi;
+    let mut quotient: u128 = 0;
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+
+    Some((quotient, remainder))
+}
+
+/// `a * b / denominator`, rounded down
+pub fn mul_div_floor(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    mul_div_rem(a, b, denominator).map(|(q, _)| q)
+}
+
+/// `a * b / denominator`, rounded up
+pub fn mul_div_ceil(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    mul_div_rem(a, b, denominator).and_then(|(q, r)| if r > 0 { q.checked_add(1) } else { Some(q) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_mul_matches_u128_products() {
+        assert_eq!(full_mul(0, u128::MAX), (0, 0));
+        assert_eq!(full_mul(3, 7), (0, 21));
+        assert_eq!(
+            full_mul(u64::MAX as u128, u64::MAX as u128),
+            (0, (u64::MAX as u128).pow(2))
+        );
+        // 2^64 * 2^64 = 2^128
+        assert_eq!(full_mul(1 << 64, 1 << 64), (1, 0));
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(full_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+    }
+
+    #[test]
+    fn mul_div_rejects_zero_denominator() {
+        assert_eq!(mul_div_rem(1, 1, 0), None);
+        assert_eq!(mul_div_floor(0, 0, 0), None);
+        assert_eq!(mul_div_ceil(u128::MAX, u128::MAX, 0), None);
+    }
+
+    #[test]
+    fn mul_div_keeps_256_bit_intermediates() {
+        // (2^128 - 1)^2 / (2^128 - 1) overflows u128 in the product only
+        assert_eq!(
+            mul_div_floor(u128::MAX, u128::MAX, u128::MAX),
+            Some(u128::MAX)
+        );
+        assert_eq!(
+            mul_div_ceil(u128::MAX, u128::MAX, u128::MAX),
+            Some(u128::MAX)
+        );
+        assert_eq!(
+            mul_div_floor(u128::MAX, 1 << 100, 1 << 100),
+            Some(u128::MAX)
+        );
+        assert_eq!(mul_div_rem(u128::MAX, 3, 2), None);
+        assert_eq!(mul_div_floor(1 << 127, 3, 4), Some(3 << 125));
+    }
+
+    #[test]
+    fn mul_div_rejects_quotients_above_u128() {
+        // the quotient is exactly 2^128
+        assert_eq!(mul_div_floor(1 << 64, 1 << 64, 1), None);
+        assert_eq!(mul_div_floor(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div_floor(u128::MAX, u128::MAX, u128::MAX - 1), None);
+        // the quotient fits, but rounding it up does not
+        assert_eq!(mul_div_floor(u128::MAX, 2, 2), Some(u128::MAX));
+        assert_eq!(
+            mul_div_rem(u128::MAX, u128::MAX - 1, u128::MAX - 1),
+            Some((u128::MAX, 0))
+        );
+        assert_eq!(
+            mul_div_ceil(u128::MAX - 1, u128::MAX, u128::MAX - 1),
+            Some(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn mul_div_rounds_in_the_requested_direction() {
+        assert_eq!(mul_div_rem(7, 3, 2), Some((10, 1)));
+        assert_eq!(mul_div_floor(7, 3, 2), Some(10));
+        assert_eq!(mul_div_ceil(7, 3, 2), Some(11));
+        // exact results are not rounded up
+        assert_eq!(mul_div_ceil(6, 3, 2), Some(9));
+        assert_eq!(mul_div_floor(1, 1, 3), Some(0));
+        assert_eq!(mul_div_ceil(1, 1, 3), Some(1));
+        // the remainder of the long division path
+        let a = u128::MAX;
+        let b = 1 << 64;
+        let d = (1 << 64) + 3;
+        let (q, r) = mul_div_rem(a, b, d).unwrap();
+        assert!(r > 0 && r < d);
+        assert_eq!(mul_div_ceil(a, b, d), Some(q + 1));
+        // q * d + r = a * b
+        let (hi, lo) = full_mul(q, d);
+        let (lo, carry) = lo.overflowing_add(r);
+        assert_eq!((hi + carry as u128, lo), full_mul(a, b));
+    }
+}
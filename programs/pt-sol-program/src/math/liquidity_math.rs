@@ -0,0 +1,272 @@
+//! Concentrated-liquidity math on Q64.64 square-root prices
+//!
+//! Between two square-root prices `sa < sb`, `L` units of liquidity hold
+//!
+//! `a = L * (sb - sa) / (sa * sb)` of token A and `b = L * (sb - sa)` of token B
+//!
+//! Liquidity is kept below `2^64` so `L * 2^64` fits in a `u128`. Every
+//! function rounds in the pool's favour: amounts the pool receives are rounded
+//! up, amounts it pays out are rounded down.
+
+use super::{mul_div_ceil, mul_div_floor};
+
+/// `1.0` in Q64.64
+pub const Q64: u128 = 1 << 64;
+
+/// 基点的分母
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Amount of token A between two square-root prices
+pub fn amount_a_delta(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u128> {
+    let (lower, upper) = (
+        sqrt_price_0.min(sqrt_price_1),
+        sqrt_price_0.max(sqrt_price_1),
+    );
+    if lower == 0 {
+        return None;
+    }
+
+    // L * 2^64 * (sb - sa) / sb / sa
+    let numerator = liquidity.checked_mul(Q64)?;
+    if round_up {
+        mul_div_ceil(numerator, upper - lower, upper).and_then(|v| mul_div_ceil(v, 1, lower))
+    } else {
+        mul_div_floor(numerator, upper - lower, upper).map(|v| v / lower)
+    }
+}
+
+/// Amount of token B between two square-root prices
+pub fn amount_b_delta(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u128> {
+    let (lower, upper) = (
+        sqrt_price_0.min(sqrt_price_1),
+        sqrt_price_0.max(sqrt_price_1),
+    );
+
+    // L * (sb - sa) / 2^64
+    if round_up {
+        mul_div_ceil(liquidity, upper - lower, Q64)
+    } else {
+        mul_div_floor(liquidity, upper - lower, Q64)
+    }
+}
+
+/// The square-root price after `amount` of token A (price goes down) or token
+/// B (price goes up) is added to the pool
+pub fn next_sqrt_price_from_input(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u128,
+    a_to_b: bool,
+) -> Option<u128> {
+    if amount == 0 {
+        return Some(sqrt_price);
+    }
+
+    if a_to_b {
+        // L * s / (L + a * s), rounded up
+        let numerator = liquidity.checked_mul(Q64)?;
+        if let Some(denominator) = amount
+            .checked_mul(sqrt_price)
+            .and_then(|product| numerator.checked_add(product))
+        {
+            return mul_div_ceil(numerator, sqrt_price, denominator);
+        }
+        // L / (L / s + a), when `a * s` does not fit
+        let denominator = (numerator / sqrt_price).checked_add(amount)?;
+        mul_div_ceil(numerator, 1, denominator)
+    } else {
+        // s + b / L, rounded down
+        sqrt_price.checked_add(mul_div_floor(amount, Q64, liquidity)?)
+    }
+}
+
+/// The result of swapping within a single range of constant liquidity
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    pub amount_in: u128,
+    pub amount_out: u128,
+    pub fee_amount: u128,
+}
+
+/// Swaps up to `amount_remaining` (including the fee) from `sqrt_price_current`
+/// towards `sqrt_price_target`, stopping early if the input runs out
+pub fn compute_swap_step(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u128,
+    fee_bps: u16,
+) -> Option<SwapStep> {
+    let a_to_b = sqrt_price_current >= sqrt_price_target;
+    let fee_bps = fee_bps as u128;
+
+    let amount_remaining_less_fee =
+        mul_div_floor(amount_remaining, BPS_DENOMINATOR - fee_bps, BPS_DENOMINATOR)?;
+    let amount_in_to_target = if a_to_b {
+        amount_a_delta(sqrt_price_target, sqrt_price_current, liquidity, true)?
+    } else {
+        amount_b_delta(sqrt_price_current, sqrt_price_target, liquidity, true)?
+    };
+
+    // 输入足够到达目标价格, 否则求出输入用完时的价格
+    let reached_target = amount_remaining_less_fee >= amount_in_to_target;
+    let sqrt_price_next = if reached_target {
+        sqrt_price_target
+    } else {
+        next_sqrt_price_from_input(
+            sqrt_price_current,
+            liquidity,
+            amount_remaining_less_fee,
+            a_to_b,
+        )?
+    };
+
+    let (amount_in, amount_out) = if a_to_b {
+        (
+            amount_a_delta(sqrt_price_next, sqrt_price_current, liquidity, true)?,
+            amount_b_delta(sqrt_price_next, sqrt_price_current, liquidity, false)?,
+        )
+    } else {
+        (
+            amount_b_delta(sqrt_price_current, sqrt_price_next, liquidity, true)?,
+            amount_a_delta(sqrt_price_current, sqrt_price_next, liquidity, false)?,
+        )
+    };
+
+    // 输入用完时, 剩下的部分全部作为手续费
+    let fee_amount = if reached_target {
+        mul_div_ceil(amount_in, fee_bps, BPS_DENOMINATOR - fee_bps)?
+    } else {
+        amount_remaining.checked_sub(amount_in)?
+    };
+
+    Some(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIQUIDITY: u128 = 1_000_000_000;
+
+    #[test]
+    fn amounts_match_the_closed_form() {
+        // between prices 1 and 4 (square roots 1 and 2): a = L * (1 - 1/2), b = L * (2 - 1)
+        let (lower, upper) = (Q64, 2 * Q64);
+        assert_eq!(
+            amount_a_delta(lower, upper, LIQUIDITY, false),
+            Some(LIQUIDITY / 2)
+        );
+        assert_eq!(
+            amount_a_delta(lower, upper, LIQUIDITY, true),
+            Some(LIQUIDITY / 2)
+        );
+        assert_eq!(
+            amount_b_delta(lower, upper, LIQUIDITY, false),
+            Some(LIQUIDITY)
+        );
+        assert_eq!(
+            amount_b_delta(lower, upper, LIQUIDITY, true),
+            Some(LIQUIDITY)
+        );
+    }
+
+    #[test]
+    fn amounts_do_not_depend_on_the_order_of_the_prices() {
+        let (lower, upper) = (Q64, 3 * Q64 / 2);
+        for round_up in [false, true] {
+            assert_eq!(
+                amount_a_delta(lower, upper, LIQUIDITY, round_up),
+                amount_a_delta(upper, lower, LIQUIDITY, round_up)
+            );
+            assert_eq!(
+                amount_b_delta(lower, upper, LIQUIDITY, round_up),
+                amount_b_delta(upper, lower, LIQUIDITY, round_up)
+            );
+        }
+    }
+
+    #[test]
+    fn amounts_round_in_the_pool_favour() {
+        // 1/3 of the way up, so neither amount is exact
+        let (lower, upper) = (Q64, Q64 + Q64 / 3);
+        let a_down = amount_a_delta(lower, upper, LIQUIDITY, false).unwrap();
+        let a_up = amount_a_delta(lower, upper, LIQUIDITY, true).unwrap();
+        assert_eq!(a_up, a_down + 1);
+        let b_down = amount_b_delta(lower, upper, LIQUIDITY, false).unwrap();
+        let b_up = amount_b_delta(lower, upper, LIQUIDITY, true).unwrap();
+        assert_eq!(b_up, b_down + 1);
+    }
+
+    #[test]
+    fn degenerate_ranges() {
+        assert_eq!(amount_a_delta(0, Q64, LIQUIDITY, false), None);
+        assert_eq!(amount_a_delta(Q64, Q64, LIQUIDITY, true), Some(0));
+        assert_eq!(amount_b_delta(Q64, Q64, LIQUIDITY, true), Some(0));
+        assert_eq!(amount_b_delta(Q64, 2 * Q64, 0, true), Some(0));
+    }
+
+    #[test]
+    fn input_moves_the_price_in_the_right_direction() {
+        let price = Q64;
+        assert_eq!(
+            next_sqrt_price_from_input(price, LIQUIDITY, 0, true),
+            Some(price)
+        );
+
+        // token A in pushes the price down, and never further than the input pays for
+        let down = next_sqrt_price_from_input(price, LIQUIDITY, 1_000, true).unwrap();
+        assert!(down < price);
+        assert!(amount_a_delta(down, price, LIQUIDITY, true).unwrap() <= 1_000);
+
+        // token B in pushes the price up, and never further than the input pays for
+        let up = next_sqrt_price_from_input(price, LIQUIDITY, 1_000, false).unwrap();
+        assert!(up > price);
+        assert!(amount_b_delta(price, up, LIQUIDITY, true).unwrap() <= 1_000);
+    }
+
+    #[test]
+    fn swap_step_stops_at_the_target_with_enough_input() {
+        let step = compute_swap_step(Q64, Q64 / 2, LIQUIDITY, u64::MAX as u128, 30).unwrap();
+        assert_eq!(step.sqrt_price_next, Q64 / 2);
+        // a = L * (2 - 1), b = L * (1 - 1/2)
+        assert_eq!(step.amount_in, LIQUIDITY);
+        assert_eq!(step.amount_out, LIQUIDITY / 2);
+        // the fee is charged on top of the input, rounded up
+        assert_eq!(step.fee_amount, (LIQUIDITY * 30).div_ceil(10_000 - 30));
+    }
+
+    #[test]
+    fn swap_step_spends_all_input_before_the_target() {
+        for (amount, a_to_b) in [(1_000u128, true), (1_000, false), (1, true)] {
+            let target = if a_to_b { Q64 / 2 } else { 2 * Q64 };
+            let step = compute_swap_step(Q64, target, LIQUIDITY, amount, 30).unwrap();
+            assert_ne!(step.sqrt_price_next, target);
+            // the whole input is used, the remainder as fee
+            assert_eq!(step.amount_in + step.fee_amount, amount);
+            assert!(step.amount_out <= step.amount_in);
+        }
+    }
+
+    #[test]
+    fn swap_step_without_fee_charges_none() {
+        let step = compute_swap_step(Q64, 2 * Q64, LIQUIDITY, u64::MAX as u128, 0).unwrap();
+        assert_eq!(step.fee_amount, 0);
+        assert_eq!(step.amount_in, LIQUIDITY);
+    }
+}
@@ -0,0 +1,10 @@
+//! Integer math helpers shared by the pool pricing functions
+pub mod fixed_point;
+pub mod full_math;
+pub mod liquidity_math;
+pub mod stable_math;
+pub mod tick_math;
+pub mod weighted_math;
+
+pub use fixed_point::*;
+pub use full_math::*;
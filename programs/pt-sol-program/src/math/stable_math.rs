@@ -0,0 +1,217 @@
+//! StableSwap (Curve-style) math for two assets
+//!
+//! The invariant `D` satisfies
+//!
+//! `A * n^n * Σx + D = A * D * n^n + D^(n+1) / (n^n * Πx)`
+//!
+//! with `n = 2`. A large amplification coefficient `A` flattens the curve
+//! around the peg, a small one moves it towards the constant product. Both
+//! balances have to be in the same precision, and `D` and `y` are found with
+//! Newton's method, the same way the Curve contracts do.
+
+use super::mul_div_floor;
+
+/// Number of assets in the invariant
+const N: u128 = 2;
+
+/// Upper bound of Newton iterations before giving up
+const MAX_ITERATIONS: usize = 255;
+
+/// Newton's method stops within a couple of units of the exact result, so
+/// callers keep this margin in the pool when turning `y` into a swap amount
+pub const ROUNDING_MARGIN: u128 = 4;
+
+/// Computes the invariant `D` of the balances `x` and `y`
+pub fn compute_d(amp: u128, x: u128, y: u128) -> Option<u128> {
+    let s = x.checked_add(y)?;
+    if s == 0 {
+        return Some(0);
+    }
+
+    let ann = amp.checked_mul(N)?;
+    let mut d = s;
+    for _ in 0..MAX_ITERATIONS {
+        // D_P = D^(n+1) / (n^n * Πx)
+        let d_p = mul_div_floor(d, d, x.checked_mul(N)?)?;
+        let d_p = mul_div_floor(d_p, d, y.checked_mul(N)?)?;
+
+        // D = (Ann * S + n * D_P) * D / ((Ann - 1) * D + (n + 1) * D_P)
+        let d_prev = d;
+        let numerator = ann.checked_mul(s)?.checked_add(d_p.checked_mul(N)?)?;
+        let denominator = ann
+            .checked_sub(1)?
+            .checked_mul(d)?
+            .checked_add(d_p.checked_mul(N + 1)?)?;
+        d = mul_div_floor(numerator, d, denominator)?;
+
+        if d.abs_diff(d_prev) <= 1 {
+            return Some(d);
+        }
+    }
+
+    None
+}
+
+/// Computes the balance `y` of the other asset that keeps the invariant at `d`
+/// when the balance of one asset is `x`
+pub fn compute_y(amp: u128, x: u128, d: u128) -> Option<u128> {
+    let ann = amp.checked_mul(N)?;
+
+    // c = D^(n+1) / (n^n * x * Ann), kept as `D^2 / (n * x)` and divided by
+    // `n * Ann` together with the Newton denominator so it cannot overflow
+    let c = mul_div_floor(d, d, x.checked_mul(N)?)?;
+    let c_denominator = ann.checked_mul(N)?;
+    // b = x + D / Ann
+    let b = x.checked_add(d / ann)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        // y = (y^2 + c) / (2y + b - D)
+        let y_prev = y;
+        let denominator = y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?;
+        y = mul_div_floor(y, y, denominator)?.checked_add(mul_div_floor(
+            c,
+            d,
+            c_denominator.checked_mul(denominator)?,
+        )?)?;
+
+        if y.abs_diff(y_prev) <= 1 {
+            return Some(y);
+        }
+    }
+
+    None
+}
+
+/// Marginal price of `x` in `y` (how much `y` one unit of `x` is worth at the
+/// current balances), in WAD
+///
+/// `-dy/dx = (Ann + D^3 / (4x^2 y)) / (Ann + D^3 / (4x y^2))`, computed as
+/// `(k + y) / (k + x)` with `k = 4 * Ann * x^2 * y^2 / D^3`
+pub fn spot_price(amp: u128, x: u128, y: u128) -> Option<u128> {
+    let d = compute_d(amp, x, y)?;
+    if d == 0 {
+        return None;
+    }
+    let ann = amp.checked_mul(N)?;
+
+    let q = mul_div_floor(x, y, d)?;
+    let k = mul_div_floor(q, q, d)?
+        .checked_mul(ann)?
+        .checked_mul(N * N)?;
+    mul_div_floor(k.checked_add(y)?, super::WAD, k.checked_add(x)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::WAD;
+
+    const BALANCE: u128 = 1_000_000 * WAD;
+
+    // the amplification coefficients at the ends of the allowed range and in between
+    const AMPS: [u128; 4] = [1, 10, 100, 1_000_000];
+
+    // integer square root, rounded down
+    fn sqrt(x: u128) -> u128 {
+        let mut r = (x as f64).sqrt() as u128;
+        while r * r > x {
+            r -= 1;
+        }
+        while (r + 1) * (r + 1) <= x {
+            r += 1;
+        }
+        r
+    }
+
+    #[test]
+    fn d_of_balanced_reserves_is_their_sum() {
+        for amp in AMPS {
+            let d = compute_d(amp, BALANCE, BALANCE).unwrap();
+            assert!(d.abs_diff(2 * BALANCE) <= 1, "amp {}: {}", amp, d);
+        }
+    }
+
+    #[test]
+    fn d_of_imbalanced_reserves_lies_between_product_and_sum() {
+        for amp in AMPS {
+            for ratio in [2, 10, 1_000] {
+                let (x, y) = (BALANCE, BALANCE / ratio);
+                let d = compute_d(amp, x, y).unwrap();
+                // the constant-product invariant 2 * sqrt(xy) and the constant-sum x + y
+                assert!(d >= 2 * sqrt(x * (y / WAD)) * sqrt(WAD), "amp {}", amp);
+                assert!(d <= x + y, "amp {}", amp);
+            }
+        }
+    }
+
+    #[test]
+    fn d_approaches_the_sum_as_amp_grows() {
+        let (x, y) = (BALANCE, BALANCE / 2);
+        let mut previous = 0;
+        for amp in AMPS {
+            let d = compute_d(amp, x, y).unwrap();
+            assert!(d > previous);
+            previous = d;
+        }
+        assert!((x + y) - previous < (x + y) / 1_000_000);
+    }
+
+    #[test]
+    fn y_recovers_the_balance_d_was_computed_from() {
+        for amp in AMPS {
+            for ratio in [1, 2, 10, 1_000] {
+                let (x, y) = (BALANCE, BALANCE / ratio);
+                let d = compute_d(amp, x, y).unwrap();
+                let computed = compute_y(amp, x, d).unwrap();
+                assert!(
+                    computed.abs_diff(y) <= ROUNDING_MARGIN,
+                    "amp {}, ratio {}: {} != {}",
+                    amp,
+                    ratio,
+                    computed,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn y_decreases_as_x_grows() {
+        for amp in AMPS {
+            let d = compute_d(amp, BALANCE, BALANCE).unwrap();
+            let mut previous = u128::MAX;
+            for x in [BALANCE, BALANCE * 11 / 10, BALANCE * 2, BALANCE * 10] {
+                let y = compute_y(amp, x, d).unwrap();
+                assert!(y < previous, "amp {}", amp);
+                previous = y;
+            }
+        }
+    }
+
+    #[test]
+    fn spot_price_is_one_at_the_peg_and_falls_with_imbalance() {
+        for amp in AMPS {
+            let price = spot_price(amp, BALANCE, BALANCE).unwrap();
+            assert!(price.abs_diff(WAD) <= 1, "amp {}: {}", amp, price);
+
+            // x is abundant, so it is worth less than one y, and less so for a small amp
+            let price = spot_price(amp, BALANCE, BALANCE / 2).unwrap();
+            assert!(price < WAD, "amp {}: {}", amp, price);
+        }
+        let flat = spot_price(1_000_000, BALANCE, BALANCE / 2).unwrap();
+        let curved = spot_price(1, BALANCE, BALANCE / 2).unwrap();
+        assert!(flat > curved);
+    }
+
+    #[test]
+    fn degenerate_inputs_return_none() {
+        // an empty pool has a zero invariant and no price
+        assert_eq!(compute_d(100, 0, 0), Some(0));
+        assert_eq!(spot_price(100, 0, 0), None);
+        // one empty side, or an amplification coefficient of 0
+        assert_eq!(compute_d(100, BALANCE, 0), None);
+        assert_eq!(compute_d(0, BALANCE, BALANCE), None);
+        assert_eq!(compute_y(100, 0, 2 * BALANCE), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Conversion between ticks and Q64.64 square-root prices
+//!
+//! The price at tick `i` is `1.0001^i`, so a tick is a 0.01% step in price.
+//! Concentrated-liquidity pools keep `sqrt(price)` as a Q64.64 fixed-point
+//! number, i.e. `sqrt(price) * 2^64` in a `u128`.
+
+use super::full_mul;
+
+/// The lowest tick whose square-root price fits in Q64.64
+pub const MIN_TICK: i32 = -443_636;
+
+/// The highest tick whose square-root price fits in Q64.64
+pub const MAX_TICK: i32 = 443_636;
+
+/// `sqrt_price_at_tick(MIN_TICK)`
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+
+/// `sqrt_price_at_tick(MAX_TICK)`
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_521_066_979_257_578_248_091;
+
+/// `1.0001^(-2^i / 2)` in Q64.64, one factor per bit of `|tick|`
+const FACTORS: [u128; 19] = [
+    0xfffcb933bd6fad38,
+    0xfff97272373d4132,
+    0xfff2e50f5f656933,
+    0xffe5caca7e10e4e6,
+    0xffcb9843d60f615a,
+    0xff973b41fa98c081,
+    0xff2ea16466c96a38,
+    0xfe5dee046a99a2a8,
+    0xfcbe86c7900a88af,
+    0xf987a7253ac41317,
+    0xf3392b0822b70006,
+    0xe7159475a2c29b74,
+    0xd097f3bdfd2022b9,
+    0xa9f746462d870fe0,
+    0x70d869a156d2a1b9,
+    0x31be135f97d08fda,
+    0x09aa508b5b7a84e2,
+    0x005d6af8dedb8119,
+    0x00002216e584f5fa,
+];
+
+/// `sqrt(1.0001^tick)` in Q64.64
+pub fn sqrt_price_at_tick(tick: i32) -> Option<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+
+    // sqrt(1.0001^-|tick|) = Π sqrt(1.0001^(-2^i)) over the set bits of |tick|
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio: u128 = 1 << 64;
+    for (i, factor) in FACTORS.iter().enumerate() {
+        if abs_tick & (1 << i) != 0 {
+            let (hi, lo) = full_mul(ratio, *factor);
+            ratio = (hi << 64) | (lo >> 64);
+        }
+    }
+
+    // 1 / ratio for positive ticks
+    if tick > 0 {
+        ratio = u128::MAX / ratio;
+    }
+
+    Some(ratio)
+}
+
+/// The greatest tick whose square-root price is not above `sqrt_price_x64`
+pub fn tick_at_sqrt_price(sqrt_price_x64: u128) -> Option<i32> {
+    if !(MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64) {
+        return None;
+    }
+
+    // 二分查找: sqrt_price_at_tick(low) <= sqrt_price_x64 < sqrt_price_at_tick(high)
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK + 1);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price_x64 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q64: u128 = 1 << 64;
+
+    #[test]
+    fn tick_zero_is_price_one() {
+        assert_eq!(sqrt_price_at_tick(0), Some(Q64));
+        assert_eq!(tick_at_sqrt_price(Q64), Some(0));
+    }
+
+    #[test]
+    fn sqrt_price_matches_known_values() {
+        // sqrt(1.0001^tick) * 2^64, computed to 80 significant digits and truncated
+        for (tick, expected) in [
+            (1, 18_447_666_387_855_959_850u128),
+            (-1, 18_445_821_805_675_392_311),
+            (100, 18_539_204_128_674_405_812),
+            (-100, 18_354_745_142_194_483_563),
+            (10_000, 30_412_779_051_191_548_722),
+        ] {
+            let actual = sqrt_price_at_tick(tick).unwrap();
+            // within one part in 10^15
+            assert!(
+                actual.abs_diff(expected) <= expected / 1_000_000_000_000_000,
+                "tick {}: {} != {}",
+                tick,
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn bounds_match_the_constants() {
+        assert_eq!(sqrt_price_at_tick(MIN_TICK), Some(MIN_SQRT_PRICE_X64));
+        assert_eq!(sqrt_price_at_tick(MAX_TICK), Some(MAX_SQRT_PRICE_X64));
+        assert_eq!(sqrt_price_at_tick(MIN_TICK - 1), None);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK + 1), None);
+
+        assert_eq!(tick_at_sqrt_price(MIN_SQRT_PRICE_X64), Some(MIN_TICK));
+        assert_eq!(tick_at_sqrt_price(MAX_SQRT_PRICE_X64), Some(MAX_TICK));
+        assert_eq!(tick_at_sqrt_price(MIN_SQRT_PRICE_X64 - 1), None);
+        assert_eq!(tick_at_sqrt_price(MAX_SQRT_PRICE_X64 + 1), None);
+    }
+
+    #[test]
+    fn sqrt_price_is_strictly_increasing() {
+        let mut previous = 0;
+        for tick in (MIN_TICK..=MAX_TICK).step_by(997) {
+            let price = sqrt_price_at_tick(tick).unwrap();
+            assert!(price > previous, "tick {}", tick);
+            previous = price;
+        }
+        // and between neighbours around the middle of the range
+        for tick in -1_000..1_000 {
+            assert!(sqrt_price_at_tick(tick + 1) > sqrt_price_at_tick(tick));
+        }
+    }
+
+    #[test]
+    fn opposite_ticks_are_reciprocal() {
+        for tick in [1, 10, 1_000, 100_000, MAX_TICK] {
+            let up = sqrt_price_at_tick(tick).unwrap();
+            let down = sqrt_price_at_tick(-tick).unwrap();
+            // up * down ~ 2^128, i.e. the product's high word is 1 or just below
+            let (hi, lo) = full_mul(up, down);
+            assert!(hi == 1 || (hi == 0 && lo > u128::MAX - up), "tick {}", tick);
+        }
+    }
+
+    #[test]
+    fn tick_round_trips_through_the_sqrt_price() {
+        for tick in (MIN_TICK..MAX_TICK)
+            .step_by(7_919)
+            .chain([-1, 0, 1, MAX_TICK - 1])
+        {
+            let price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(price), Some(tick));
+            // any price below the next tick still belongs to this tick
+            let next = sqrt_price_at_tick(tick + 1).unwrap();
+            assert_eq!(tick_at_sqrt_price(next - 1), Some(tick));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Weighted-product swap math
+//!
+//! A weighted pool keeps `∏ B_i^{w_i}` constant, where `w_i` is the
+//! normalized weight of asset `i`. Only the ratio of the two weights of a swap
+//! matters, so callers can pass the pool's stored weights without normalizing
+//! them first.
+
+use super::{mul_div_ceil, mul_div_floor, pow_up, WAD};
+
+/// `out = Bo * (1 - (Bi / (Bi + ai))^(wi / wo))`, rounded down
+///
+/// `amount_in` is the amount already net of the swap fee, in the same unit as
+/// `balance_in`
+pub fn out_given_in(
+    balance_in: u128,
+    weight_in: u128,
+    balance_out: u128,
+    weight_out: u128,
+    amount_in: u128,
+) -> Option<u128> {
+    // Bi / (Bi + ai), rounded up
+    let base = mul_div_ceil(balance_in, WAD, balance_in.checked_add(amount_in)?)?;
+    // wi / wo, rounded down: base < 1, so a smaller exponent gives a larger power
+    let exponent = mul_div_floor(weight_in, WAD, weight_out)?;
+    let power = pow_up(base, exponent)?;
+
+    mul_div_floor(balance_out, WAD.saturating_sub(power), WAD)
+}
+
+/// `ai = Bi * ((Bo / (Bo - ao))^(wo / wi) - 1)`, rounded up
+///
+/// The result does not include the swap fee yet. Returns `None` if `amount_out`
+/// would drain the pool
+pub fn in_given_out(
+    balance_in: u128,
+    weight_in: u128,
+    balance_out: u128,
+    weight_out: u128,
+    amount_out: u128,
+) -> Option<u128> {
+    // Bo / (Bo - ao), rounded up
+    let base = mul_div_ceil(balance_out, WAD, balance_out.checked_sub(amount_out)?)?;
+    // wo / wi, rounded up: base >= 1, so a larger exponent gives a larger power
+    let exponent = mul_div_ceil(weight_out, WAD, weight_in)?;
+    let power = pow_up(base, exponent)?;
+
+    mul_div_ceil(balance_in, power.saturating_sub(WAD), WAD)
+}
+
+/// Marginal price of the "in" asset in the "out" asset, `(Bo / wo) / (Bi / wi)`,
+/// in WAD
+pub fn spot_price(
+    balance_in: u128,
+    weight_in: u128,
+    balance_out: u128,
+    weight_out: u128,
+) -> Option<u128> {
+    mul_div_floor(
+        mul_div_floor(balance_out, WAD, balance_in)?,
+        weight_in,
+        weight_out,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::MAX_POW_RELATIVE_ERROR;
+
+    // (Bi, wi, Bo, wo, amount, out_given_in, in_given_out), the expected values
+    // computed to 60 significant digits and truncated
+    const CASES: [(u128, u128, u128, u128, u128, u128, u128); 4] = [
+        // equal weights: the constant-product curve
+        (
+            1_000_000_000_000,
+            50,
+            1_000_000_000_000,
+            50,
+            10_000_000_000,
+            9_900_990_099,
+            10_101_010_101,
+        ),
+        // 80/20
+        (
+            1_000_000_000_000,
+            80,
+            500_000_000_000,
+            20,
+            10_000_000_000,
+            19_509_827_758,
+            5_063_452_997,
+        ),
+        // 20/80
+        (
+            500_000_000_000,
+            20,
+            1_000_000_000_000,
+            80,
+            10_000_000_000,
+            4_938_422_520,
+            20_510_177_842,
+        ),
+        // unnormalized weights and a trade of 40% of the balance
+        (
+            1_000_000_000_000,
+            100,
+            3_000_000_000_000,
+            300,
+            400_000_000_000,
+            318_289_394_710,
+            536_185_707_783,
+        ),
+    ];
+
+    // the error of `pow_up` on a result scaled by `balance`
+    fn tolerance(balance: u128) -> u128 {
+        balance * MAX_POW_RELATIVE_ERROR / WAD + 2
+    }
+
+    #[test]
+    fn out_given_in_matches_known_values_rounded_down() {
+        for (bi, wi, bo, wo, amount, expected, _) in CASES {
+            let out = out_given_in(bi, wi, bo, wo, amount).unwrap();
+            assert!(out <= expected, "{} > {}", out, expected);
+            assert!(expected - out <= tolerance(bo), "{} << {}", out, expected);
+        }
+    }
+
+    #[test]
+    fn in_given_out_matches_known_values_rounded_up() {
+        for (bi, wi, bo, wo, amount, _, expected) in CASES {
+            let amount_in = in_given_out(bi, wi, bo, wo, amount).unwrap();
+            assert!(amount_in > expected, "{} <= {}", amount_in, expected);
+            assert!(
+                amount_in - expected <= tolerance(bi),
+                "{} >> {}",
+                amount_in,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_never_pays_out_more() {
+        for (bi, wi, bo, wo, amount, _, _) in CASES {
+            // paying the quoted amount in receives at least the amount out
+            let amount_in = in_given_out(bi, wi, bo, wo, amount).unwrap();
+            assert!(out_given_in(bi, wi, bo, wo, amount_in).unwrap() >= amount);
+            // and quoting the amount received costs at most what was paid
+            let out = out_given_in(bi, wi, bo, wo, amount).unwrap();
+            assert!(in_given_out(bi, wi, bo, wo, out).unwrap() <= amount);
+        }
+    }
+
+    #[test]
+    fn tiny_trades_round_against_the_trader() {
+        assert_eq!(out_given_in(WAD, 1, WAD, 1, 1), Some(0));
+        assert!(in_given_out(WAD, 1, WAD, 1, 1).unwrap() >= 1);
+        assert_eq!(out_given_in(WAD, 1, WAD, 1, 0), Some(0));
+    }
+
+    #[test]
+    fn in_given_out_rejects_draining_the_pool() {
+        assert_eq!(in_given_out(WAD, 1, WAD, 1, WAD), None);
+        assert_eq!(in_given_out(WAD, 1, WAD, 1, WAD + 1), None);
+    }
+
+    #[test]
+    fn spot_price_matches_known_values() {
+        // equal weights: Bo / Bi
+        assert_eq!(spot_price(1_000, 50, 2_000, 50), Some(2 * WAD));
+        // 80/20: (Bo / wo) / (Bi / wi) = (500 / 20) / (1000 / 80) = 2
+        assert_eq!(spot_price(1_000, 80, 500, 20), Some(2 * WAD));
+        // 1/3, rounded down
+        assert_eq!(spot_price(3, 1, 1, 1), Some(WAD / 3));
+        assert_eq!(spot_price(0, 1, 1, 1), None);
+    }
+}
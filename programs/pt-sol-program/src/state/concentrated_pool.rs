@@ -0,0 +1,732 @@
+//! Concentrated-liquidity pool account state
+use anchor_lang::prelude::*;
+
+use crate::error::SwapProgramError;
+use crate::math::{liquidity_math, mul_div_floor, tick_math};
+use crate::state::{LiquidityPool, PoolSigner, PoolStatus};
+
+/// The `ConcentratedPool` state - a two-asset pool where liquidity providers
+/// choose the price range their liquidity is active in
+///
+/// The price is the amount of `mint_b` per unit of `mint_a`, kept as its square
+/// root in Q64.64. Liquidity is added in positions between two ticks, and only
+/// the positions whose range contains the current price take part in swaps.
+/// Like a `LiquidityPool`, it has an `authority` who can pause it and take a
+/// protocol share of the swap fees for the `treasury`
+#[account]
+pub struct ConcentratedPool {
+    /// 池子的编号, 用于derive PDA
+    pub pool_id: u64,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// 可以暂停池子和设置协议手续费的管理员
+    pub authority: Pubkey,
+    /// 领取协议手续费的账户
+    pub treasury: Pubkey,
+    /// 仓位的tick必须是`tick_spacing`的整数倍
+    pub tick_spacing: u16,
+    /// 交易手续费, 以基点(bps)表示
+    pub fee_bps: u16,
+    /// 交易手续费中归协议所有的部分, 以基点表示, 其余归流动性提供者所有
+    pub protocol_fee_bps: u16,
+    /// 尚未领取的协议手续费
+    pub protocol_fees_a: u64,
+    pub protocol_fees_b: u64,
+    /// 池子的状态, 由管理员暂停或恢复
+    pub status: PoolStatus,
+    /// 当前价格的平方根, Q64.64
+    pub sqrt_price_x64: u128,
+    /// 当前价格所在的tick
+    pub tick_current: i32,
+    /// 当前价格下有效的流动性
+    pub liquidity: u128,
+    /// 每单位流动性累计的手续费, Q64.64
+    pub fee_growth_global_a_x64: u128,
+    pub fee_growth_global_b_x64: u128,
+    pub bump: u8,
+}
+
+/// One initialized tick boundary of the liquidity positions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Tick {
+    pub initialized: bool,
+    /// 价格从左向右穿过该tick时有效流动性的变化
+    pub liquidity_net: i128,
+    /// 以该tick为边界的所有仓位的流动性之和
+    pub liquidity_gross: u128,
+    /// tick另一侧(相对于当前价格)累计的手续费, Q64.64
+    pub fee_growth_outside_a_x64: u128,
+    pub fee_growth_outside_b_x64: u128,
+}
+
+/// A fixed run of `TickArray::SIZE` ticks, `tick_spacing` apart, starting at
+/// `start_tick_index`
+#[account]
+pub struct TickArray {
+    pub pool: Pubkey,
+    pub start_tick_index: i32,
+    pub ticks: [Tick; TickArray::SIZE],
+}
+
+/// A liquidity position between `tick_lower` and `tick_upper`
+#[account]
+pub struct Position {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    /// 上次结算时仓位范围内每单位流动性累计的手续费, Q64.64
+    pub fee_growth_inside_a_last_x64: u128,
+    pub fee_growth_inside_b_last_x64: u128,
+    /// 尚未领取的手续费
+    pub fees_owed_a: u64,
+    pub fees_owed_b: u64,
+    pub bump: u8,
+}
+
+impl ConcentratedPool {
+    // 集中流动性池子的seed prefix, 用于derive PDA
+    pub const SEED_PREFIX: &'static str = "concentrated_pool";
+
+    // discrimator + u64 + Pubkey + Pubkey + Pubkey + Pubkey + u16 + u16 + u16 + u64 + u64
+    // + PoolStatus + u128 + i32 + u128 + u128 + u128 + u8
+    pub const SPACE: usize =
+        8 + 8 + 32 + 32 + 32 + 32 + 2 + 2 + 2 + 8 + 8 + 1 + 16 + 4 + 16 + 16 + 16 + 1;
+
+    // tick间隔的上限
+    pub const MAX_TICK_SPACING: u16 = 1_000;
+
+    // 流动性的上限, 保证`L * 2^64`不会溢出
+    pub const MAX_LIQUIDITY: u128 = u64::MAX as u128;
+
+    // create a new concentrated-liquidity pool state, `authority` also receives the protocol fees
+    pub fn new(
+        pool_id: u64,
+        mints: (Pubkey, Pubkey),
+        authority: Pubkey,
+        tick_spacing: u16,
+        fee_bps: u16,
+        sqrt_price_x64: u128,
+        bump: u8,
+    ) -> Result<Self> {
+        if tick_spacing == 0 || tick_spacing > Self::MAX_TICK_SPACING {
+            return Err(SwapProgramError::InvalidTickSpacing.into());
+        }
+        let tick_current = tick_math::tick_at_sqrt_price(sqrt_price_x64)
+            .filter(|_| sqrt_price_x64 < tick_math::MAX_SQRT_PRICE_X64)
+            .ok_or(SwapProgramError::InvalidSqrtPrice)?;
+
+        Ok(Self {
+            pool_id,
+            mint_a: mints.0,
+            mint_b: mints.1,
+            authority,
+            treasury: authority,
+            tick_spacing,
+            fee_bps,
+            protocol_fee_bps: 0,
+            protocol_fees_a: 0,
+            protocol_fees_b: 0,
+            status: PoolStatus::Active,
+            sqrt_price_x64,
+            tick_current,
+            liquidity: 0,
+            fee_growth_global_a_x64: 0,
+            fee_growth_global_b_x64: 0,
+            bump,
+        })
+    }
+
+    // 池子是否允许swap、开仓和存入流动性
+    pub fn is_active(&self) -> bool {
+        self.status == PoolStatus::Active
+    }
+
+    // 池子是否允许取回流动性和领取手续费
+    pub fn allows_withdrawals(&self) -> bool {
+        self.status != PoolStatus::Paused
+    }
+
+    // 一个`TickArray`覆盖的tick数
+    pub fn tick_array_span(&self) -> i32 {
+        self.tick_spacing as i32 * TickArray::SIZE as i32
+    }
+
+    // 包含`tick`的`TickArray`的起始tick
+    pub fn tick_array_start(&self, tick: i32) -> i32 {
+        tick.div_euclid(self.tick_array_span()) * self.tick_array_span()
+    }
+
+    // 检查仓位的tick范围
+    pub fn check_tick_range(&self, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        let spacing = self.tick_spacing as i32;
+        if tick_lower >= tick_upper
+            || tick_lower < tick_math::MIN_TICK
+            || tick_upper > tick_math::MAX_TICK
+            || tick_lower % spacing != 0
+            || tick_upper % spacing != 0
+        {
+            return Err(SwapProgramError::InvalidTickRange.into());
+        }
+        Ok(())
+    }
+
+    // 仓位范围内每单位流动性累计的手续费
+    fn fee_growth_inside(&self, lower: (i32, &Tick), upper: (i32, &Tick)) -> (u128, u128) {
+        let (tick_lower, lower) = lower;
+        let (tick_upper, upper) = upper;
+        let global_a = self.fee_growth_global_a_x64;
+        let global_b = self.fee_growth_global_b_x64;
+
+        // 下边界以下和上边界以上累计的手续费
+        let (below_a, below_b) = if self.tick_current >= tick_lower {
+            (
+                lower.fee_growth_outside_a_x64,
+                lower.fee_growth_outside_b_x64,
+            )
+        } else {
+            (
+                global_a.wrapping_sub(lower.fee_growth_outside_a_x64),
+                global_b.wrapping_sub(lower.fee_growth_outside_b_x64),
+            )
+        };
+        let (above_a, above_b) = if self.tick_current < tick_upper {
+            (
+                upper.fee_growth_outside_a_x64,
+                upper.fee_growth_outside_b_x64,
+            )
+        } else {
+            (
+                global_a.wrapping_sub(upper.fee_growth_outside_a_x64),
+                global_b.wrapping_sub(upper.fee_growth_outside_b_x64),
+            )
+        };
+
+        (
+            global_a.wrapping_sub(below_a).wrapping_sub(above_a),
+            global_b.wrapping_sub(below_b).wrapping_sub(above_b),
+        )
+    }
+
+    /// 将`liquidity_delta`加到仓位及其两个边界tick上, 并结算仓位的手续费
+    ///
+    /// 返回仓位需要存入(`liquidity_delta > 0`, 向上取整)或取回(向下取整)的两种资产数额
+    pub fn modify_position(
+        &mut self,
+        position: &mut Position,
+        ticks: (&mut Tick, &mut Tick),
+        liquidity_delta: i128,
+    ) -> Result<(u64, u64)> {
+        let (lower, upper) = ticks;
+        let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+        let globals = (self.fee_growth_global_a_x64, self.fee_growth_global_b_x64);
+
+        lower.update(
+            tick_lower,
+            self.tick_current,
+            liquidity_delta,
+            false,
+            globals,
+        )?;
+        upper.update(
+            tick_upper,
+            self.tick_current,
+            liquidity_delta,
+            true,
+            globals,
+        )?;
+
+        let fee_growth_inside = self.fee_growth_inside((tick_lower, lower), (tick_upper, upper));
+        position.update(liquidity_delta, fee_growth_inside)?;
+
+        // 不再被任何仓位使用的tick
+        if lower.liquidity_gross == 0 {
+            *lower = Tick::default();
+        }
+        if upper.liquidity_gross == 0 {
+            *upper = Tick::default();
+        }
+
+        // 按当前价格相对于仓位范围的位置计算两种资产的数额
+        let round_up = liquidity_delta > 0;
+        let liquidity = liquidity_delta.unsigned_abs();
+        let sqrt_price_lower =
+            tick_math::sqrt_price_at_tick(tick_lower).ok_or(SwapProgramError::InvalidTickRange)?;
+        let sqrt_price_upper =
+            tick_math::sqrt_price_at_tick(tick_upper).ok_or(SwapProgramError::InvalidTickRange)?;
+
+        let (amount_a, amount_b) = if self.tick_current < tick_lower {
+            (
+                liquidity_math::amount_a_delta(
+                    sqrt_price_lower,
+                    sqrt_price_upper,
+                    liquidity,
+                    round_up,
+                ),
+                Some(0),
+            )
+        } else if self.tick_current < tick_upper {
+            self.liquidity = add_liquidity_delta(self.liquidity, liquidity_delta)?;
+            (
+                liquidity_math::amount_a_delta(
+                    self.sqrt_price_x64,
+                    sqrt_price_upper,
+                    liquidity,
+                    round_up,
+                ),
+                liquidity_math::amount_b_delta(
+                    sqrt_price_lower,
+                    self.sqrt_price_x64,
+                    liquidity,
+                    round_up,
+                ),
+            )
+        } else {
+            (
+                Some(0),
+                liquidity_math::amount_b_delta(
+                    sqrt_price_lower,
+                    sqrt_price_upper,
+                    liquidity,
+                    round_up,
+                ),
+            )
+        };
+
+        let to_u64 = |amount: Option<u128>| {
+            amount
+                .and_then(|a| u64::try_from(a).ok())
+                .ok_or(SwapProgramError::InvalidArithmetic)
+        };
+        Ok((to_u64(amount_a)?, to_u64(amount_b)?))
+    }
+
+    /// 用`amount_in`交换另一种资产, 价格沿着`tick_arrays`依次穿过已初始化的tick
+    ///
+    /// `tick_arrays`按交易方向排列, 第一个包含当前价格。返回收到的数额
+    pub fn swap(
+        &mut self,
+        tick_arrays: &mut [Account<TickArray>],
+        amount_in: u64,
+        a_to_b: bool,
+    ) -> Result<u64> {
+        let mut amount_remaining = amount_in as u128;
+        let mut amount_out: u128 = 0;
+        let mut array_index = 0;
+
+        while amount_remaining > 0 {
+            // 向左搜索包含当前tick的数组, 向右搜索包含下一个tick的数组
+            let expected_start = self.tick_array_start(if a_to_b {
+                self.tick_current
+            } else {
+                self.tick_current + 1
+            });
+            if tick_arrays
+                .get(array_index)
+                .is_some_and(|array| array.start_tick_index != expected_start)
+            {
+                array_index += 1;
+            }
+            let tick_array = tick_arrays
+                .get_mut(array_index)
+                .filter(|array| array.start_tick_index == expected_start)
+                .ok_or(SwapProgramError::InvalidTickArray)?;
+
+            // 下一个已初始化的tick, 或者数组的边界
+            let (next_tick, initialized) =
+                tick_array.next_initialized_tick(self.tick_current, self.tick_spacing, a_to_b)?;
+            let (next_tick, initialized) =
+                if (tick_math::MIN_TICK..=tick_math::MAX_TICK).contains(&next_tick) {
+                    (next_tick, initialized)
+                } else {
+                    (
+                        next_tick.clamp(tick_math::MIN_TICK, tick_math::MAX_TICK),
+                        false,
+                    )
+                };
+            let sqrt_price_target = tick_math::sqrt_price_at_tick(next_tick)
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+            let step = liquidity_math::compute_swap_step(
+                self.sqrt_price_x64,
+                sqrt_price_target,
+                self.liquidity,
+                amount_remaining,
+                self.fee_bps,
+            )
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+            amount_remaining -= step.amount_in + step.fee_amount;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+            // 手续费中协议的部分记录在`protocol_fees_a`/`protocol_fees_b`中,
+            // 其余按有效流动性分配
+            if self.liquidity > 0 {
+                let protocol_fee = step.fee_amount * self.protocol_fee_bps as u128
+                    / LiquidityPool::BPS_DENOMINATOR as u128;
+                self.accrue_protocol_fee(protocol_fee, a_to_b)?;
+                let fee_growth = mul_div_floor(
+                    step.fee_amount - protocol_fee,
+                    liquidity_math::Q64,
+                    self.liquidity,
+                )
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+                if a_to_b {
+                    self.fee_growth_global_a_x64 =
+                        self.fee_growth_global_a_x64.wrapping_add(fee_growth);
+                } else {
+                    self.fee_growth_global_b_x64 =
+                        self.fee_growth_global_b_x64.wrapping_add(fee_growth);
+                }
+            }
+
+            self.sqrt_price_x64 = step.sqrt_price_next;
+            if step.sqrt_price_next == sqrt_price_target {
+                if initialized {
+                    let liquidity_net = tick_array
+                        .tick_mut(next_tick, self.tick_spacing)?
+                        .cross(self.fee_growth_global_a_x64, self.fee_growth_global_b_x64);
+                    self.liquidity = add_liquidity_delta(
+                        self.liquidity,
+                        if a_to_b {
+                            -liquidity_net
+                        } else {
+                            liquidity_net
+                        },
+                    )?;
+                }
+
+                // 向右到达未初始化的数组边界时, 留到下一个数组中再穿过
+                self.tick_current = if a_to_b || !initialized {
+                    next_tick - 1
+                } else {
+                    next_tick
+                };
+
+                // 到达价格的边界
+                if amount_remaining > 0
+                    && (next_tick == tick_math::MIN_TICK || next_tick == tick_math::MAX_TICK)
+                {
+                    return Err(SwapProgramError::InvalidSwapNotEnoughLiquidity.into());
+                }
+            } else {
+                self.tick_current = tick_math::tick_at_sqrt_price(step.sqrt_price_next)
+                    .ok_or(SwapProgramError::InvalidArithmetic)?;
+            }
+        }
+
+        u64::try_from(amount_out).map_err(|_| SwapProgramError::InvalidArithmetic.into())
+    }
+
+    // 记录支付的资产(`a_to_b`时为资产A)中归协议所有的手续费
+    fn accrue_protocol_fee(&mut self, fee: u128, a_to_b: bool) -> Result<()> {
+        let protocol_fees = if a_to_b {
+            &mut self.protocol_fees_a
+        } else {
+            &mut self.protocol_fees_b
+        };
+        *protocol_fees = u64::try_from(fee)
+            .ok()
+            .and_then(|fee| protocol_fees.checked_add(fee))
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        Ok(())
+    }
+}
+
+impl PoolSigner for ConcentratedPool {
+    fn signer_seeds(&self) -> Vec<Vec<u8>> {
+        vec![
+            ConcentratedPool::SEED_PREFIX.as_bytes().to_vec(),
+            self.pool_id.to_le_bytes().to_vec(),
+            vec![self.bump],
+        ]
+    }
+}
+
+impl Tick {
+    // 按仓位的流动性变化更新tick, `upper`表示该tick是仓位的上边界
+    pub fn update(
+        &mut self,
+        tick: i32,
+        tick_current: i32,
+        liquidity_delta: i128,
+        upper: bool,
+        fee_growth_global: (u128, u128),
+    ) -> Result<()> {
+        let liquidity_gross = add_liquidity_delta(self.liquidity_gross, liquidity_delta)?;
+
+        // 第一次初始化时, 假设此前所有的手续费都在当前价格一侧累计
+        if self.liquidity_gross == 0 && liquidity_gross > 0 {
+            if tick <= tick_current {
+                self.fee_growth_outside_a_x64 = fee_growth_global.0;
+                self.fee_growth_outside_b_x64 = fee_growth_global.1;
+            }
+            self.initialized = true;
+        }
+
+        self.liquidity_gross = liquidity_gross;
+        self.liquidity_net = if upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        }
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+        Ok(())
+    }
+
+    // 价格穿过该tick, 返回`liquidity_net`
+    pub fn cross(&mut self, fee_growth_global_a: u128, fee_growth_global_b: u128) -> i128 {
+        self.fee_growth_outside_a_x64 =
+            fee_growth_global_a.wrapping_sub(self.fee_growth_outside_a_x64);
+        self.fee_growth_outside_b_x64 =
+            fee_growth_global_b.wrapping_sub(self.fee_growth_outside_b_x64);
+        self.liquidity_net
+    }
+}
+
+impl TickArray {
+    // tick数组的seed prefix, 与pool和起始tick一起derive PDA
+    pub const SEED_PREFIX: &'static str = "tick_array";
+
+    // 每个数组中的tick数
+    pub const SIZE: usize = 32;
+
+    // discrimator + Pubkey + i32 + [Tick; SIZE]
+    pub const SPACE: usize = 8 + 32 + 4 + (1 + 16 + 16 + 16 + 16) * Self::SIZE;
+
+    // `tick`在数组中的位置
+    fn tick_offset(&self, tick: i32, tick_spacing: u16) -> Result<usize> {
+        let spacing = tick_spacing as i32;
+        let offset = tick - self.start_tick_index;
+        if tick % spacing != 0 || offset < 0 || offset >= spacing * Self::SIZE as i32 {
+            return Err(SwapProgramError::InvalidTickArray.into());
+        }
+        Ok((offset / spacing) as usize)
+    }
+
+    pub fn tick(&self, tick: i32, tick_spacing: u16) -> Result<Tick> {
+        Ok(self.ticks[self.tick_offset(tick, tick_spacing)?])
+    }
+
+    pub fn tick_mut(&mut self, tick: i32, tick_spacing: u16) -> Result<&mut Tick> {
+        let offset = self.tick_offset(tick, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+
+    /// 从`tick_current`开始在数组中搜索下一个已初始化的tick: 向左包括`tick_current`,
+    /// 向右不包括。没有找到时返回数组的边界和`false`
+    pub fn next_initialized_tick(
+        &self,
+        tick_current: i32,
+        tick_spacing: u16,
+        a_to_b: bool,
+    ) -> Result<(i32, bool)> {
+        let spacing = tick_spacing as i32;
+        let first = self.start_tick_index / spacing;
+        let tick_at = |offset: usize| self.start_tick_index + offset as i32 * spacing;
+
+        if a_to_b {
+            let offset = tick_current.div_euclid(spacing) - first;
+            if !(0..Self::SIZE as i32).contains(&offset) {
+                return Err(SwapProgramError::InvalidTickArray.into());
+            }
+            Ok((0..=offset as usize)
+                .rev()
+                .find(|i| self.ticks[*i].initialized)
+                .map_or((self.start_tick_index, false), |i| (tick_at(i), true)))
+        } else {
+            let offset = tick_current.div_euclid(spacing) + 1 - first;
+            if !(0..=Self::SIZE as i32).contains(&offset) {
+                return Err(SwapProgramError::InvalidTickArray.into());
+            }
+            Ok((offset as usize..Self::SIZE)
+                .find(|i| self.ticks[*i].initialized)
+                .map_or((tick_at(Self::SIZE), false), |i| (tick_at(i), true)))
+        }
+    }
+}
+
+impl Position {
+    // 仓位的seed prefix, 与pool、owner和tick范围一起derive PDA
+    pub const SEED_PREFIX: &'static str = "position";
+
+    // discrimator + Pubkey + Pubkey + i32 + i32 + u128 + u128 + u128 + u64 + u64 + u8
+    pub const SPACE: usize = 8 + 32 + 32 + 4 + 4 + 16 + 16 + 16 + 8 + 8 + 1;
+
+    // 结算仓位范围内新累计的手续费, 然后更新仓位的流动性
+    pub fn update(&mut self, liquidity_delta: i128, fee_growth_inside: (u128, u128)) -> Result<()> {
+        let (inside_a, inside_b) = fee_growth_inside;
+        let owed = |inside: u128, last: u128| {
+            mul_div_floor(
+                inside.wrapping_sub(last),
+                self.liquidity,
+                liquidity_math::Q64,
+            )
+            .map_or(u64::MAX, |fee| u64::try_from(fee).unwrap_or(u64::MAX))
+        };
+
+        // 手续费超过`u64`时截断, 不能因此锁住仓位
+        self.fees_owed_a = self
+            .fees_owed_a
+            .saturating_add(owed(inside_a, self.fee_growth_inside_a_last_x64));
+        self.fees_owed_b = self
+            .fees_owed_b
+            .saturating_add(owed(inside_b, self.fee_growth_inside_b_last_x64));
+        self.fee_growth_inside_a_last_x64 = inside_a;
+        self.fee_growth_inside_b_last_x64 = inside_b;
+
+        self.liquidity = add_liquidity_delta(self.liquidity, liquidity_delta)?;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.liquidity == 0 && self.fees_owed_a == 0 && self.fees_owed_b == 0
+    }
+}
+
+/// Adds a signed liquidity change, keeping the result within
+/// `ConcentratedPool::MAX_LIQUIDITY`
+fn add_liquidity_delta(liquidity: u128, liquidity_delta: i128) -> Result<u128> {
+    let liquidity = if liquidity_delta >= 0 {
+        liquidity.checked_add(liquidity_delta as u128)
+    } else {
+        liquidity.checked_sub(liquidity_delta.unsigned_abs())
+    };
+    liquidity
+        .filter(|l| *l <= ConcentratedPool::MAX_LIQUIDITY)
+        .ok_or(SwapProgramError::InvalidLiquidity.into())
+}
+
+/// Writes a position's two ticks back to their tick arrays
+///
+/// Both ticks can live in the same tick array account, in which case Anchor
+/// holds two copies of it and serializes both on exit, so the second copy is
+/// kept identical to the first
+pub fn store_ticks<'info>(
+    tick_arrays: (
+        &mut Account<'info, TickArray>,
+        &mut Account<'info, TickArray>,
+    ),
+    lower: (i32, Tick),
+    upper: (i32, Tick),
+    tick_spacing: u16,
+) -> Result<()> {
+    let (tick_array_lower, tick_array_upper) = tick_arrays;
+
+    *tick_array_lower.tick_mut(lower.0, tick_spacing)? = lower.1;
+    if tick_array_lower.key() == tick_array_upper.key() {
+        *tick_array_lower.tick_mut(upper.0, tick_spacing)? = upper.1;
+        tick_array_upper.ticks = tick_array_lower.ticks;
+    } else {
+        *tick_array_upper.tick_mut(upper.0, tick_spacing)? = upper.1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn liquidity_delta_adds_and_removes() {
+        assert_eq!(add_liquidity_delta(100, 50).unwrap(), 150);
+        assert_eq!(add_liquidity_delta(100, -50).unwrap(), 50);
+        assert_eq!(add_liquidity_delta(100, -100).unwrap(), 0);
+        assert_eq!(add_liquidity_delta(100, 0).unwrap(), 100);
+    }
+
+    #[test]
+    fn liquidity_delta_rejects_underflow_and_the_cap() {
+        assert!(add_liquidity_delta(100, -101).is_err());
+        assert!(add_liquidity_delta(0, i128::MIN).is_err());
+        let max = ConcentratedPool::MAX_LIQUIDITY;
+        assert_eq!(add_liquidity_delta(max - 1, 1).unwrap(), max);
+        assert!(add_liquidity_delta(max, 1).is_err());
+    }
+
+    #[test]
+    fn tick_net_liquidity_is_signed_by_boundary() {
+        let (mut lower, mut upper) = (Tick::default(), Tick::default());
+        lower.update(-10, 0, 1_000, false, (0, 0)).unwrap();
+        upper.update(10, 0, 1_000, true, (0, 0)).unwrap();
+        // crossing the lower tick upwards adds the liquidity, crossing the upper removes it
+        assert_eq!(lower.liquidity_net, 1_000);
+        assert_eq!(upper.liquidity_net, -1_000);
+        assert_eq!(lower.liquidity_gross, 1_000);
+        assert_eq!(upper.liquidity_gross, 1_000);
+
+        // removing part of it moves both back towards zero
+        lower.update(-10, 0, -400, false, (0, 0)).unwrap();
+        upper.update(10, 0, -400, true, (0, 0)).unwrap();
+        assert_eq!(lower.liquidity_net, 600);
+        assert_eq!(upper.liquidity_net, -600);
+        assert_eq!(upper.liquidity_gross, 600);
+
+        // and more than the tick holds fails
+        assert!(lower.update(-10, 0, -601, false, (0, 0)).is_err());
+    }
+
+    #[test]
+    fn tick_initialization_records_fee_growth_below_the_price() {
+        let (mut below, mut above) = (Tick::default(), Tick::default());
+        below.update(-10, 0, 1, false, (7, 9)).unwrap();
+        above.update(10, 0, 1, true, (7, 9)).unwrap();
+        assert!(below.initialized && above.initialized);
+        assert_eq!(
+            (
+                below.fee_growth_outside_a_x64,
+                below.fee_growth_outside_b_x64
+            ),
+            (7, 9)
+        );
+        assert_eq!(
+            (
+                above.fee_growth_outside_a_x64,
+                above.fee_growth_outside_b_x64
+            ),
+            (0, 0)
+        );
+
+        // crossing flips the outside growth to the other side
+        assert_eq!(below.cross(10, 20), 1);
+        assert_eq!(
+            (
+                below.fee_growth_outside_a_x64,
+                below.fee_growth_outside_b_x64
+            ),
+            (3, 11)
+        );
+    }
+
+    #[test]
+    fn position_settles_fees_before_changing_liquidity() {
+        let mut position = Position {
+            pool: Pubkey::default(),
+            owner: Pubkey::default(),
+            tick_lower: -10,
+            tick_upper: 10,
+            liquidity: 0,
+            fee_growth_inside_a_last_x64: 0,
+            fee_growth_inside_b_last_x64: 0,
+            fees_owed_a: 0,
+            fees_owed_b: 0,
+            bump: 0,
+        };
+        position.update(1_000, (0, 0)).unwrap();
+        assert_eq!(position.liquidity, 1_000);
+
+        // 3 and 5 per unit of liquidity accrue on the old liquidity only
+        let growth = (3 * liquidity_math::Q64, 5 * liquidity_math::Q64);
+        position.update(-1_000, growth).unwrap();
+        assert_eq!(position.liquidity, 0);
+        assert_eq!((position.fees_owed_a, position.fees_owed_b), (3_000, 5_000));
+        assert!(!position.is_empty());
+
+        assert!(position.update(-1, growth).is_err());
+    }
+}
@@ -1,39 +1,571 @@
 //! Swap program account state
 use anchor_lang::{prelude::*, system_program};
-use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
-use std::ops::{Add, Div, Mul};
+use anchor_spl::token::{spl_token::native_mint, Token};
+use anchor_spl::token_2022::{
+    self,
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Mint as MintState,
+    },
+    Token2022,
+};
+use anchor_spl::token_interface::{
+    burn, close_account, mint_to, sync_native, transfer_checked, Burn, CloseAccount, Mint, MintTo,
+    SyncNative, TokenAccount, TokenInterface, TransferChecked,
+};
 
 use crate::error::SwapProgramError;
+use crate::events::{DepositEvent, SwapEvent, WithdrawEvent};
+use crate::math::{ln, mul_div_ceil, mul_div_floor, pow_down, stable_math, weighted_math, WAD};
 
 /// The `LiquidityPool` state - the inner data of the program-derived address
 /// that will be our Liquidity Pool
+///
+/// Each pool is derived from `SEED_PREFIX` and its `pool_id`, so any number of
+/// independent pools can exist side by side
 #[account]
 pub struct LiquidityPool {
+    /// 池子的编号, 用于derive PDA
+    pub pool_id: u64,
     pub assets: Vec<Pubkey>,
+    /// 每一种资产在权重调整开始时的权重, 与`assets`一一对应。资产的归一化权重为`w_i / Σw`
+    pub weights: Vec<u64>,
+    /// 每一种资产权重调整的目标, 与`assets`一一对应
+    pub target_weights: Vec<u64>,
+    /// 权重从`weights`线性调整到`target_weights`的开始和结束slot
+    pub weight_ramp_start_slot: u64,
+    pub weight_ramp_end_slot: u64,
+    /// 流动性份额(LP share)的铸币地址, 铸币权限为pool
+    pub lp_mint: Pubkey,
+    /// 池子的管理员, 可以调整手续费等参数
+    pub authority: Pubkey,
+    /// 等待接受的新管理员, 由`accept_pool_authority`完成转移
+    pub pending_authority: Option<Pubkey>,
+    /// 交易手续费, 以基点(bps)表示
+    pub fee_bps: u16,
+    /// 交易手续费中归协议所有的部分, 以手续费的基点(bps)表示
+    pub protocol_fee_bps: u16,
+    /// 可以领取协议手续费的账户
+    pub treasury: Pubkey,
+    /// 每一种资产尚未领取的协议手续费, 与`assets`一一对应
+    pub protocol_fees: Vec<u64>,
+    /// 每一种资产属于流动性提供者的数额, 与`assets`一一对应。只由存入、取回、swap和闪电贷
+    /// 的手续费改变, 直接转入pool的Token账户的余额不计入其中
+    pub reserves: Vec<u64>,
+    /// 每一种资产铸币的精度, 与`assets`一一对应, StableSwap按相同的精度计算不变量
+    pub decimals: Vec<u8>,
+    /// 池子的状态, 管理员可以暂停池子
+    pub status: PoolStatus,
+    /// 定价曲线, 在创建池子时选择
+    pub curve: CurveType,
+    /// StableSwap曲线的放大系数, 管理员可以在一段slot内逐渐调整
+    pub amp: AmpRamp,
+    /// `price_accumulators`最后一次累加的slot
+    pub oracle_slot: u64,
+    /// 每一种资产的价格累加器, 与`assets`一一对应, 用于TWAP预言机
+    pub price_accumulators: Vec<PriceAccumulator>,
     pub bump: u8,
 }
 
+/// The pricing curve of a `LiquidityPool`, chosen when the pool is created
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveType {
+    /// Weighted constant product, priced with the pool's `weights`
+    ConstantProduct,
+    /// Curve-style StableSwap for pegged assets, priced with the pool's `amp`
+    StableSwap,
+}
+
+/// The price accumulator of one asset of a `LiquidityPool`
+///
+/// The spot price of asset `i` in asset `j` is `(R_j / w_j) / (R_i / w_i)`, so
+/// summing `ln(R / w)` over slots for every asset is enough to derive the
+/// geometric-mean price of any pair over any window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct PriceAccumulator {
+    /// 资产当前的`ln(reserve / weight)`, WAD
+    pub ln_value: i128,
+    /// `ln_value`按slot的累加, 溢出时回绕
+    pub cumulative: i128,
+    /// 资产最近一次上架的slot, 下架再上架时累加器从0重新开始, 之前的观测不能再使用
+    pub listed_slot: u64,
+}
+
+/// The StableSwap amplification coefficient, moving linearly from
+/// `initial_amp` to `target_amp` between `start_slot` and `end_slot`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AmpRamp {
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+impl AmpRamp {
+    // 固定的放大系数
+    pub fn new(amp: u64) -> Self {
+        Self {
+            initial_amp: amp,
+            target_amp: amp,
+            start_slot: 0,
+            end_slot: 0,
+        }
+    }
+
+    // 当前slot的放大系数
+    pub fn current(&self, slot: u64) -> u64 {
+        ramp(
+            (self.initial_amp, self.target_amp),
+            (self.start_slot, self.end_slot),
+            slot,
+        )
+    }
+}
+
+// 在`slots` (开始, 结束) 之间从`values`的初始值线性变化到目标值, 向初始值取整
+fn ramp(values: (u64, u64), slots: (u64, u64), slot: u64) -> u64 {
+    let (initial, target) = values;
+    let (start_slot, end_slot) = slots;
+    if slot >= end_slot {
+        return target;
+    }
+    if slot <= start_slot {
+        return initial;
+    }
+
+    let elapsed = (slot - start_slot) as u128;
+    let duration = (end_slot - start_slot) as u128;
+    if target >= initial {
+        let change = (target - initial) as u128 * elapsed / duration;
+        initial + change as u64
+    } else {
+        let change = (initial - target) as u128 * elapsed / duration;
+        initial - change as u64
+    }
+}
+
+/// The status of a `LiquidityPool`, set by its authority with the `pause` and
+/// `unpause` instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolStatus {
+    /// Every instruction is allowed
+    Active,
+    /// Every instruction that moves assets is halted
+    Paused,
+    /// Swaps, deposits and stakes are halted, but users can still withdraw
+    /// their liquidity and unstake
+    WithdrawOnly,
+}
+
 impl LiquidityPool {
     // LP的seed prefix, 用于derive PDA
     pub const SEED_PREFIX: &'static str = "liquidity_pool";
 
-    // discrimator + Vec(empty) + u8
-    pub const SPACE: usize = 8 + 4 + 1;
+    // discrimator + u64 + Vec(empty) + Vec(empty) + Vec(empty) + u64 + u64 + Pubkey + Pubkey
+    // + Option<Pubkey> + u16 + u16 + Pubkey + Vec(empty) + Vec(empty) + Vec(empty) + PoolStatus
+    // + CurveType + AmpRamp + u64 + Vec(empty) + u8
+    pub const SPACE: usize =
+        8 + 8 + 4 + 4 + 4 + 8 + 8 + 32 + 32 + 33 + 2 + 2 + 32 + 4 + 4 + 4 + 1 + 1 + 32 + 8 + 4 + 1;
+
+    // 每新增一种资产需要的空间: Pubkey + u64(权重) + u64(目标权重) + u64(协议手续费) + u64(储备)
+    // + u8(精度) + PriceAccumulator
+    pub const ASSET_SPACE: usize = 32 + 8 + 8 + 8 + 8 + 1 + 40;
+
+    // StableSwap池子的资产数量上限, 不变量`D`由两种资产的储备决定
+    pub const MAX_STABLE_SWAP_ASSETS: usize = 2;
+
+    // 新资产的默认权重, 所有资产权重相同时即为等权重池
+    pub const DEFAULT_WEIGHT: u64 = 100;
+
+    // 每一种资产的归一化权重下限 1%
+    pub const MIN_WEIGHT_PERCENT: u64 = 1;
+
+    // LP份额铸币的精度
+    pub const LP_MINT_DECIMALS: u8 = 9;
+
+    // 第一次存入时锁定在池子自己的LP账户中的份额, 使份额的价格不能被抬高到让之后的存入取整为0
+    pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+    // 交易手续费的上限 10%, 以基点(bps)表示
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
+    // 基点的分母
+    pub const BPS_DENOMINATOR: u64 = 10_000;
+
+    // StableSwap放大系数的范围
+    pub const MIN_AMP: u64 = 1;
+    pub const MAX_AMP: u64 = 1_000_000;
+
+    // 一次调整中放大系数最多变化的倍数
+    pub const MAX_AMP_CHANGE: u64 = 10;
+
+    // 调整放大系数和权重所需的最少slot数, 约为一天
+    pub const MIN_RAMP_SLOTS: u64 = 216_000;
 
     // create a new liquidity pool state
-    pub fn new(bump: u8) -> Self {
+    pub fn new(
+        pool_id: u64,
+        lp_mint: Pubkey,
+        authority: Pubkey,
+        fee_bps: u16,
+        curve: CurveType,
+        amp: u64,
+        bump: u8,
+    ) -> Self {
         Self {
+            pool_id,
             assets: vec![],
+            weights: vec![],
+            target_weights: vec![],
+            weight_ramp_start_slot: 0,
+            weight_ramp_end_slot: 0,
+            lp_mint,
+            authority,
+            pending_authority: None,
+            fee_bps,
+            protocol_fee_bps: 0,
+            treasury: authority,
+            protocol_fees: vec![],
+            reserves: vec![],
+            decimals: vec![],
+            status: PoolStatus::Active,
+            curve,
+            amp: AmpRamp::new(amp),
+            oracle_slot: 0,
+            price_accumulators: vec![],
             bump,
         }
     }
+
+    // 池子是否允许swap、存入流动性和stake
+    pub fn is_active(&self) -> bool {
+        self.status == PoolStatus::Active
+    }
+
+    // 池子是否允许取回流动性和unstake
+    pub fn allows_withdrawals(&self) -> bool {
+        self.status != PoolStatus::Paused
+    }
+
+    // 资产在`assets`中的位置
+    pub fn asset_index(&self, key: &Pubkey) -> Result<usize> {
+        self.assets
+            .iter()
+            .position(|k| k == key)
+            .ok_or(SwapProgramError::InvalidAssetKey.into())
+    }
+
+    // 池子中属于流动性提供者的数额, 不包括尚未领取的协议手续费和直接转入的余额
+    pub fn reserve(&self, index: usize) -> u64 {
+        self.reserves[index]
+    }
+
+    // 存入或收到`amount`之后增加储备
+    pub fn add_reserve(&mut self, index: usize, amount: u64) -> Result<()> {
+        self.reserves[index] = self.reserves[index]
+            .checked_add(amount)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        Ok(())
+    }
+
+    // 取回或转出`amount`之后减少储备
+    pub fn sub_reserve(&mut self, index: usize, amount: u64) -> Result<()> {
+        self.reserves[index] = self.reserves[index]
+            .checked_sub(amount)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        Ok(())
+    }
+
+    // 交易支付的`pay_amount`中的手续费, 向下取整
+    pub fn fee_amount(&self, pay_amount: u64) -> u64 {
+        (pay_amount as u128 * self.fee_bps as u128 / Self::BPS_DENOMINATOR as u128) as u64
+    }
+
+    // 闪电贷借出`amount`需要支付的手续费, 与交易手续费的费率相同, 向上取整
+    pub fn flash_loan_fee(&self, amount: u64) -> Result<u64> {
+        mul_div_ceil(
+            amount as u128,
+            self.fee_bps as u128,
+            Self::BPS_DENOMINATOR as u128,
+        )
+        .map(|fee| fee as u64)
+        .ok_or(SwapProgramError::InvalidArithmetic.into())
+    }
+
+    // 记录一笔交易支付的`pay_amount`中归协议所有的手续费, 向下取整, 返回记录的数额
+    pub fn accrue_protocol_fee(&mut self, index: usize, pay_amount: u64) -> Result<u64> {
+        let fee = (pay_amount as u128 * self.fee_bps as u128 * self.protocol_fee_bps as u128
+            / (Self::BPS_DENOMINATOR as u128 * Self::BPS_DENOMINATOR as u128))
+            as u64;
+        self.protocol_fees[index] = self.protocol_fees[index]
+            .checked_add(fee)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        Ok(fee)
+    }
+
+    // 把每一种资产的`ln_value`累加到`slot`, 同一个slot内只累加一次
+    pub fn accumulate_prices(&mut self, slot: u64) {
+        let elapsed = slot.saturating_sub(self.oracle_slot) as i128;
+        if elapsed == 0 {
+            return;
+        }
+        for accumulator in self.price_accumulators.iter_mut() {
+            accumulator.cumulative = accumulator
+                .cumulative
+                .wrapping_add(accumulator.ln_value.wrapping_mul(elapsed));
+        }
+        self.oracle_slot = slot;
+    }
+
+    // 资产的储备变化之后更新它的`ln_value`, 新的价格从当前slot开始累加
+    pub fn record_price(&mut self, index: usize) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        // 上一次累加之后权重仍在调整, 所有资产的`ln_value`都已经变化
+        let ramping = self.oracle_slot < self.weight_ramp_end_slot;
+        self.accumulate_prices(slot);
+
+        if ramping {
+            for i in 0..self.assets.len() {
+                self.update_ln_value(i, slot)?;
+            }
+            Ok(())
+        } else {
+            self.update_ln_value(index, slot)
+        }
+    }
+
+    // 按`slot`的储备和权重重新计算资产的`ln_value`
+    fn update_ln_value(&mut self, index: usize, slot: u64) -> Result<()> {
+        // 储备为0时没有价格, 记为0
+        let value = mul_div_floor(
+            self.reserve(index) as u128,
+            WAD,
+            self.weight(index, slot) as u128,
+        )
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+        self.price_accumulators[index].ln_value = ln(value).unwrap_or(0);
+        Ok(())
+    }
+
+    // `base`资产以`quote`资产计价的累计对数价格, 外推到`slot`
+    pub fn price_cumulative(&self, base: usize, quote: usize, slot: u64) -> i128 {
+        let elapsed = slot.saturating_sub(self.oracle_slot) as i128;
+        let cumulative = |accumulator: &PriceAccumulator| {
+            accumulator
+                .cumulative
+                .wrapping_add(accumulator.ln_value.wrapping_mul(elapsed))
+        };
+        cumulative(&self.price_accumulators[quote])
+            .wrapping_sub(cumulative(&self.price_accumulators[base]))
+    }
+
+    // `base`和`quote`两种资产中较晚上架的slot, 预言机只能使用晚于它的观测
+    pub fn listed_slot(&self, base: usize, quote: usize) -> u64 {
+        self.price_accumulators[base]
+            .listed_slot
+            .max(self.price_accumulators[quote].listed_slot)
+    }
+
+    // 资产在`slot`的权重, 调整期间从`weights`线性变化到`target_weights`
+    pub fn weight(&self, index: usize, slot: u64) -> u64 {
+        ramp(
+            (self.weights[index], self.target_weights[index]),
+            (self.weight_ramp_start_slot, self.weight_ramp_end_slot),
+            slot,
+        )
+    }
+
+    // 除`key`以外所有资产在`slot`的权重之和
+    pub fn other_weights(&self, key: &Pubkey, slot: u64) -> u64 {
+        self.assets
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| *k != key)
+            .map(|(i, _)| self.weight(i, slot))
+            .sum()
+    }
+
+    // 检查权重: 与`assets`一一对应, 且每一种资产的归一化权重不低于`MIN_WEIGHT_PERCENT`
+    pub fn check_weights(&self, weights: &[u64]) -> Result<()> {
+        if weights.len() != self.assets.len() {
+            return Err(SwapProgramError::InvalidWeights.into());
+        }
+        let total = weights.iter().map(|w| *w as u128).sum::<u128>();
+        if weights
+            .iter()
+            .any(|w| *w == 0 || (*w as u128) * 100 < total * Self::MIN_WEIGHT_PERCENT as u128)
+        {
+            return Err(SwapProgramError::InvalidWeights.into());
+        }
+        Ok(())
+    }
+
+    // 检查StableSwap的放大系数是否在允许的范围内
+    pub fn check_amp(amp: u64) -> Result<()> {
+        if !(Self::MIN_AMP..=Self::MAX_AMP).contains(&amp) {
+            return Err(SwapProgramError::InvalidAmp.into());
+        }
+        Ok(())
+    }
+
+    // 从当前的放大系数开始, 在`end_slot`之前线性调整到`target_amp`
+    pub fn ramp_amp(&mut self, target_amp: u64, end_slot: u64, slot: u64) -> Result<()> {
+        Self::check_amp(target_amp)?;
+
+        let current_amp = self.amp.current(slot);
+        let too_fast = end_slot < slot.saturating_add(Self::MIN_RAMP_SLOTS);
+        let too_large = target_amp > current_amp.saturating_mul(Self::MAX_AMP_CHANGE)
+            || current_amp > target_amp.saturating_mul(Self::MAX_AMP_CHANGE);
+        if too_fast || too_large {
+            return Err(SwapProgramError::InvalidAmp.into());
+        }
+
+        self.amp = AmpRamp {
+            initial_amp: current_amp,
+            target_amp,
+            start_slot: slot,
+            end_slot,
+        };
+        Ok(())
+    }
+
+    // 从当前的权重开始, 在`end_slot`之前线性调整到`weights`
+    //
+    // 权重在调整期间连续变化, 每个slot的价格变化都很小, 管理员不能靠一次调整套利流动性提供者
+    pub fn ramp_weights(&mut self, weights: Vec<u64>, end_slot: u64, slot: u64) -> Result<()> {
+        self.check_weights(&weights)?;
+        if end_slot < slot.saturating_add(Self::MIN_RAMP_SLOTS) {
+            return Err(SwapProgramError::InvalidWeights.into());
+        }
+
+        // 先按调整前的权重累加价格
+        let ramping = self.oracle_slot < self.weight_ramp_end_slot;
+        self.accumulate_prices(slot);
+
+        self.weights = (0..self.assets.len())
+            .map(|i| self.weight(i, slot))
+            .collect();
+        self.target_weights = weights;
+        self.weight_ramp_start_slot = slot;
+        self.weight_ramp_end_slot = end_slot;
+
+        // 当前的权重不变, 只有上一次调整中的`ln_value`需要更新
+        if ramping {
+            for i in 0..self.assets.len() {
+                self.update_ln_value(i, slot)?;
+            }
+        }
+        Ok(())
+    }
+
+    // 按池子的曲线计算支付`pay_amount`可以收到的数额
+    // `receive`和`pay`为 (资产的位置, 铸币的精度)
+    pub fn determine_receive(
+        &self,
+        receive: (usize, u8),
+        pay: (usize, u8),
+        pay_amount: u64,
+    ) -> Result<u64> {
+        let (receive_index, receive_decimals) = receive;
+        let (pay_index, pay_decimals) = pay;
+        let slot = Clock::get()?.slot;
+
+        match self.curve {
+            CurveType::ConstantProduct => determine_swap_receive(
+                self.reserve(receive_index),
+                self.weight(receive_index, slot),
+                self.reserve(pay_index),
+                self.weight(pay_index, slot),
+                pay_amount,
+                self.fee_bps,
+            ),
+            CurveType::StableSwap => determine_stable_swap_receive(
+                (self.reserve(receive_index), receive_decimals),
+                (self.reserve(pay_index), pay_decimals),
+                pay_amount,
+                self.amp.current(slot),
+                self.fee_bps,
+            ),
+        }
+    }
+
+    // 按池子的曲线计算收到`receive_amount`需要支付的数额
+    // `receive`和`pay`为 (资产的位置, 铸币的精度)
+    pub fn determine_pay(
+        &self,
+        receive: (usize, u8),
+        pay: (usize, u8),
+        receive_amount: u64,
+    ) -> Result<u64> {
+        let (receive_index, receive_decimals) = receive;
+        let (pay_index, pay_decimals) = pay;
+        let slot = Clock::get()?.slot;
+
+        match self.curve {
+            CurveType::ConstantProduct => determine_swap_pay(
+                self.reserve(receive_index),
+                self.weight(receive_index, slot),
+                self.reserve(pay_index),
+                self.weight(pay_index, slot),
+                receive_amount,
+                self.fee_bps,
+            ),
+            CurveType::StableSwap => determine_stable_swap_pay(
+                (self.reserve(receive_index), receive_decimals),
+                (self.reserve(pay_index), pay_decimals),
+                receive_amount,
+                self.amp.current(slot),
+                self.fee_bps,
+            ),
+        }
+    }
+
+    // 不计手续费时一单位支付的资产可以换到的接收资产的数额(边际价格), WAD
+    // `receive`和`pay`为 (资产的位置, 铸币的精度)
+    pub fn determine_spot_price(&self, receive: (usize, u8), pay: (usize, u8)) -> Result<u128> {
+        let (receive_index, receive_decimals) = receive;
+        let (pay_index, pay_decimals) = pay;
+        let reserve_r = self.reserve(receive_index) as u128;
+        let reserve_p = self.reserve(pay_index) as u128;
+        let slot = Clock::get()?.slot;
+
+        match self.curve {
+            CurveType::ConstantProduct => weighted_math::spot_price(
+                reserve_p,
+                self.weight(pay_index, slot) as u128,
+                reserve_r,
+                self.weight(receive_index, slot) as u128,
+            ),
+            CurveType::StableSwap => {
+                // 换算到相同精度计算, 再换算回两种资产的原始数额
+                let (r_scale, p_scale) = stable_swap_scales(receive_decimals, pay_decimals)?;
+                stable_math::spot_price(
+                    self.amp.current(slot) as u128,
+                    reserve_p * p_scale,
+                    reserve_r * r_scale,
+                )
+                .and_then(|price| mul_div_floor(price, p_scale, r_scale))
+            }
+        }
+        .ok_or(SwapProgramError::InvalidArithmetic.into())
+    }
+
+    // 检查手续费是否在允许的范围内
+    pub fn check_fee_bps(fee_bps: u16) -> Result<()> {
+        if fee_bps > Self::MAX_FEE_BPS {
+            return Err(SwapProgramError::InvalidFee.into());
+        }
+        Ok(())
+    }
 }
 
 pub trait LiquidityPoolAccount<'info> {
     fn check_asset_key(&self, key: &Pubkey) -> Result<()>;
     fn add_asset(
         &mut self,
-        key: Pubkey,
+        mint: (Pubkey, u8),
         payer: &Signer<'info>,
         system_program: &Program<'info, System>,
     ) -> Result<()>;
@@ -46,30 +578,108 @@ pub trait LiquidityPoolAccount<'info> {
     fn fund(
         &mut self,
         deposit: (
-            &Account<'info, Mint>,
-            &Account<'info, TokenAccount>,
-            &Account<'info, TokenAccount>,
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
             u64,
         ),
+        list_asset: bool,
+        lp: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
         authority: &Signer<'info>,
         system_program: &Program<'info, System>,
-        token_program: &Program<'info, Token>,
+        token_programs: (&Interface<'info, TokenInterface>, &Program<'info, Token>),
+    ) -> Result<()>;
+    fn withdraw(
+        &mut self,
+        lp: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            u64,
+        ),
+        withdrawals: &[(
+            InterfaceAccount<'info, Mint>,
+            InterfaceAccount<'info, TokenAccount>,
+            InterfaceAccount<'info, TokenAccount>,
+        )],
+        authority: &Signer<'info>,
+        token_programs: (&Program<'info, Token>, &Program<'info, Token2022>),
     ) -> Result<()>;
     fn process_swap(
         &mut self,
         receive: (
-            &Account<'info, Mint>,
-            &Account<'info, TokenAccount>,
-            &Account<'info, TokenAccount>,
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
         ),
         pay: (
-            &Account<'info, Mint>,
-            &Account<'info, TokenAccount>,
-            &Account<'info, TokenAccount>,
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
             u64,
         ),
+        min_amount_out: u64,
         authority: &Signer<'info>,
-        token_program: &Program<'info, Token>,
+        token_programs: (
+            &Interface<'info, TokenInterface>,
+            &Interface<'info, TokenInterface>,
+        ),
+    ) -> Result<()>;
+    fn process_swap_exact_out(
+        &mut self,
+        receive: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+            u64,
+        ),
+        pay: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
+        max_amount_in: u64,
+        authority: &Signer<'info>,
+        token_programs: (
+            &Interface<'info, TokenInterface>,
+            &Interface<'info, TokenInterface>,
+        ),
+    ) -> Result<()>;
+    fn process_swap_route(
+        &mut self,
+        path: &[(
+            InterfaceAccount<'info, Mint>,
+            InterfaceAccount<'info, TokenAccount>,
+        )],
+        pay: (&InterfaceAccount<'info, TokenAccount>, u64),
+        receive: (&InterfaceAccount<'info, TokenAccount>, u64),
+        authority: &Signer<'info>,
+        token_programs: (&Program<'info, Token>, &Program<'info, Token2022>),
+    ) -> Result<()>;
+    fn record_swap(&mut self, pay: (usize, u64), receive: (usize, u64), user: Pubkey)
+        -> Result<()>;
+    fn remove_asset(
+        &mut self,
+        delist: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
+        rent_receiver: &Signer<'info>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()>;
+    fn shrink(&mut self, space_to_remove: usize, rent_receiver: &Signer<'info>) -> Result<()>;
+    fn collect_protocol_fees(
+        &mut self,
+        collect: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
+        token_program: &Interface<'info, TokenInterface>,
     ) -> Result<()>;
     fn determine_auto_fund_pool_interval(
         &self,
@@ -88,18 +698,37 @@ impl<'info> LiquidityPoolAccount<'info> for Account<'info, LiquidityPool> {
     }
 
     /// 如果资产不存在于流动性池的铸币地址列表中，则将其添加到该列表中
-    /// 如果添加了铸币地址，这将需要重新分配账户的大小，因为向量将增加一个“公钥”，其大小为 32 字节
+    /// 如果添加了铸币地址，这将需要重新分配账户的大小，因为向量将增加一个“公钥”，其大小为 32 字节，
+    /// 同时权重、协议手续费、储备和精度的向量各增加一项。新资产的权重为`DEFAULT_WEIGHT`
+    ///
+    /// `mint`为 (铸币地址, 铸币的精度)
     fn add_asset(
         &mut self,
-        key: Pubkey,
+        mint: (Pubkey, u8),
         payer: &Signer<'info>,
         system_program: &Program<'info, System>,
     ) -> Result<()> {
+        let (key, decimals) = mint;
         match self.check_asset_key(&key) {
             Ok(()) => (),
             Err(_) => {
-                self.realloc(32, payer, system_program)?;
-                self.assets.push(key)
+                // StableSwap的不变量只覆盖两种资产
+                if self.curve == CurveType::StableSwap
+                    && self.assets.len() >= LiquidityPool::MAX_STABLE_SWAP_ASSETS
+                {
+                    return Err(SwapProgramError::InvalidStableSwapAssets.into());
+                }
+                self.realloc(LiquidityPool::ASSET_SPACE, payer, system_program)?;
+                self.assets.push(key);
+                self.weights.push(LiquidityPool::DEFAULT_WEIGHT);
+                self.target_weights.push(LiquidityPool::DEFAULT_WEIGHT);
+                self.protocol_fees.push(0);
+                self.reserves.push(0);
+                self.decimals.push(decimals);
+                self.price_accumulators.push(PriceAccumulator {
+                    listed_slot: Clock::get()?.slot,
+                    ..PriceAccumulator::default()
+                });
             }
         };
 
@@ -143,74 +772,553 @@ impl<'info> LiquidityPoolAccount<'info> for Account<'info, LiquidityPool> {
     ///
     /// 在这个函数中，程序还会将铸币地址添加到`LiquidityPool` 数据中存储的铸币地址列表中
     /// （如果不存在），并重新分配账户的大小
+    ///
+    /// 流动性提供者按存入资产的价值获得LP份额。只有管理员可以上架新的资产, 上架时存入的数额
+    /// 决定新资产的初始价格, 不铸造LP份额, 所以必须用`list_asset`明确上架; 向已有资产单边存入时,
+    /// 超出按比例存入的部分与swap一样收取`fee_bps`的手续费
+    ///
+    /// 第一次存入铸造`amount`份额, 其中`MINIMUM_LIQUIDITY`锁定在池子自己的LP账户`lp_locked`中
+    ///
+    /// StableSwap池子中的两种资产都上架之后, 份额按不变量`D`的增长计算, 手续费按存入后偏离
+    /// 按比例存入的部分收取, 见`determine_stable_deposit_shares`
     fn fund(
         &mut self,
         deposit: (
-            &Account<'info, Mint>,
-            &Account<'info, TokenAccount>,
-            &Account<'info, TokenAccount>,
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
             u64,
         ),
+        list_asset: bool,
+        lp: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
         authority: &Signer<'info>,
         system_program: &Program<'info, System>,
-        token_program: &Program<'info, Token>,
+        token_programs: (&Interface<'info, TokenInterface>, &Program<'info, Token>),
     ) -> Result<()> {
         let (mint, from, to, amount) = deposit;
-        self.add_asset(mint.key(), authority, system_program)?;
+        let (lp_mint, lp_to, lp_locked) = lp;
+        let (token_program, lp_token_program) = token_programs;
+
+        // 带转账手续费的资产, pool实际收到的是扣除手续费之后的数额
+        let received = amount - determine_transfer_fee(mint, amount)?;
+
+        // 份额按存入前的池子状态计算
+        let slot = Clock::get()?.slot;
+        let (asset_index, reserve, weight) = match self.asset_index(&mint.key()) {
+            Ok(index) => (Some(index), self.reserve(index), self.weight(index, slot)),
+            Err(_) => {
+                // 上架新资产只能由管理员调用, 否则任何人都可以用没有价值的铸币换走池子中的资产
+                if authority.key() != self.authority {
+                    return Err(SwapProgramError::InvalidPoolAuthority.into());
+                }
+                (None, 0, LiquidityPool::DEFAULT_WEIGHT)
+            }
+        };
+        let listing = asset_index.is_none();
+        if listing != list_asset {
+            return Err(SwapProgramError::InvalidListing.into());
+        }
+        let other_weights = self.other_weights(&mint.key(), slot);
+
+        let (shares, taxable) = match (self.curve, asset_index) {
+            // 第一次存入, 锁定`MINIMUM_LIQUIDITY`之后剩下的份额归payer
+            _ if lp_mint.supply == 0 => (
+                received
+                    .checked_sub(LiquidityPool::MINIMUM_LIQUIDITY)
+                    .ok_or(SwapProgramError::InvalidLiquidityZeroShares)?,
+                0,
+            ),
+            // 上架新资产, 不铸造份额
+            (_, None) => (0, 0),
+            // StableSwap池子的两种资产都已上架
+            (CurveType::StableSwap, Some(index))
+                if self.assets.len() == LiquidityPool::MAX_STABLE_SWAP_ASSETS =>
+            {
+                let other = 1 - index;
+                determine_stable_deposit_shares(
+                    lp_mint.supply,
+                    (reserve, mint.decimals),
+                    (self.reserve(other), self.decimals[other]),
+                    received,
+                    self.amp.current(slot),
+                    self.fee_bps,
+                )?
+            }
+            _ => {
+                // 单边存入相当于先把其中不属于该资产权重的部分swap成其他资产, 这部分收取手续费,
+                // 否则存入再取回就是一笔不付手续费的swap
+                let taxable = determine_deposit_taxable(received, weight, other_weights)?;
+                let fee = mul_div_ceil(
+                    taxable as u128,
+                    self.fee_bps as u128,
+                    LiquidityPool::BPS_DENOMINATOR as u128,
+                )
+                .ok_or(SwapProgramError::InvalidArithmetic)? as u64;
+
+                let shares = determine_deposit_shares(
+                    lp_mint.supply,
+                    reserve,
+                    received - fee,
+                    weight,
+                    other_weights,
+                )?;
+                (shares, taxable)
+            }
+        };
+        if shares == 0 && !listing {
+            return Err(SwapProgramError::InvalidLiquidityZeroShares.into());
+        }
+
+        self.add_asset((mint.key(), mint.decimals), authority, system_program)?;
+        // 存入的手续费与swap一样, 协议的部分记录在`protocol_fees`中, 其余的归流动性提供者
+        let index = self.asset_index(&mint.key())?;
+        let protocol_fee = self.accrue_protocol_fee(index, taxable)?;
+        self.add_reserve(index, received - protocol_fee)?;
+        self.record_price(index)?;
         // payer's Token Account => pool's Token Account
-        process_transfer_to_pool(from, to, amount, authority, token_program)?;
+        process_transfer_to_pool(from, to, mint, amount, authority, token_program)?;
+        // pool => pool's LP Token Account, 永远不会被销毁
+        if lp_mint.supply == 0 {
+            process_mint_lp_shares(
+                lp_mint,
+                lp_locked,
+                LiquidityPool::MINIMUM_LIQUIDITY,
+                self,
+                lp_token_program,
+            )?;
+        }
+        // pool => payer's LP Token Account
+        if shares > 0 {
+            process_mint_lp_shares(lp_mint, lp_to, shares, self, lp_token_program)?;
+        }
+
+        emit!(DepositEvent {
+            pool: self.key(),
+            user: authority.key(),
+            mint: mint.key(),
+            amount,
+            shares,
+            reserve: self.reserve(index),
+        });
+
+        Ok(())
+    }
+
+    /// 销毁LP份额, 按份额占总供应量的比例从池子中取回每一种资产
+    ///
+    /// `withdrawals`按`assets`的顺序给出每一种资产的 (铸币账户, pool的Token账户, 接收的Token账户),
+    /// 每一种资产通过其铸币所属的Token程序转账
+    fn withdraw(
+        &mut self,
+        lp: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            u64,
+        ),
+        withdrawals: &[(
+            InterfaceAccount<'info, Mint>,
+            InterfaceAccount<'info, TokenAccount>,
+            InterfaceAccount<'info, TokenAccount>,
+        )],
+        authority: &Signer<'info>,
+        token_programs: (&Program<'info, Token>, &Program<'info, Token2022>),
+    ) -> Result<()> {
+        let (lp_mint, lp_from, shares) = lp;
+        if shares == 0 {
+            return Err(SwapProgramError::InvalidLiquidityZeroShares.into());
+        }
+
+        // 每一种资产的数额按销毁前的供应量计算, 向下取整
+        let supply = lp_mint.supply as u128;
+        let amounts = (0..withdrawals.len())
+            .map(|index| {
+                mul_div_floor(self.reserve(index) as u128, shares as u128, supply)
+                    .map(|amount| amount as u64)
+                    .ok_or(SwapProgramError::InvalidArithmetic.into())
+            })
+            .collect::<Result<Vec<u64>>>()?;
+
+        // LP份额总是由SPL Token程序铸造
+        process_burn_lp_shares(lp_mint, lp_from, shares, authority, token_programs.0)?;
+
+        for (index, amount) in amounts.iter().enumerate() {
+            self.sub_reserve(index, *amount)?;
+            self.record_price(index)?;
+        }
+
+        for ((mint, from, to), amount) in withdrawals.iter().zip(&amounts) {
+            if *amount > 0 {
+                let token_program = token_program_of(mint, token_programs)?;
+                process_transfer_from_pool(from, to, mint, *amount, self, &token_program)?;
+            }
+        }
+
+        emit!(WithdrawEvent {
+            pool: self.key(),
+            user: authority.key(),
+            shares,
+            amounts,
+        });
 
         Ok(())
     }
 
+    /// 支付的资产带转账手续费时, 按pool实际收到的数额定价; 接收的资产带转账手续费时,
+    /// `min_amount_out`与payer实际收到的数额比较
     fn process_swap(
         &mut self,
         receive: (
-            &Account<'info, Mint>,
-            &Account<'info, TokenAccount>,
-            &Account<'info, TokenAccount>,
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
         ),
         pay: (
-            &Account<'info, Mint>,
-            &Account<'info, TokenAccount>,
-            &Account<'info, TokenAccount>,
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
             u64,
         ),
+        min_amount_out: u64,
         authority: &Signer<'info>,
-        token_program: &Program<'info, Token>,
+        token_programs: (
+            &Interface<'info, TokenInterface>,
+            &Interface<'info, TokenInterface>,
+        ),
     ) -> Result<()> {
         // (From, To)
         let (receive_mint, pool_recieve, payer_recieve) = receive;
-        self.check_asset_key(&receive_mint.key())?;
+        let receive_index = self.asset_index(&receive_mint.key())?;
         // (From, To)
         let (pay_mint, payer_pay, pool_pay, pay_amount) = pay;
-        self.check_asset_key(&pay_mint.key())?;
+        let pay_index = self.asset_index(&pay_mint.key())?;
+        let (receive_token_program, pay_token_program) = token_programs;
 
         // 计算swap接收到的数额
-        let receive_amount = determine_swap_receive(
-            pool_recieve.amount,
-            receive_mint.decimals,
-            pool_pay.amount,
-            pay_mint.decimals,
-            pay_amount,
+        let pool_pay_amount = pay_amount - determine_transfer_fee(pay_mint, pay_amount)?;
+        let receive_amount = self.determine_receive(
+            (receive_index, receive_mint.decimals),
+            (pay_index, pay_mint.decimals),
+            pool_pay_amount,
         )?;
+        let payer_receive_amount =
+            receive_amount - determine_transfer_fee(receive_mint, receive_amount)?;
 
         // Process the swap
-        if receive_amount == 0 {
+        if payer_receive_amount == 0 {
             Err(SwapProgramError::InvalidSwapNotEnoughPay.into())
+        } else if payer_receive_amount < min_amount_out {
+            Err(SwapProgramError::InvalidSwapSlippageExceeded.into())
         } else {
-            process_transfer_to_pool(payer_pay, pool_pay, pay_amount, authority, token_program)?;
+            self.record_swap(
+                (pay_index, pool_pay_amount),
+                (receive_index, receive_amount),
+                authority.key(),
+            )?;
+            process_transfer_to_pool(
+                payer_pay,
+                pool_pay,
+                pay_mint,
+                pay_amount,
+                authority,
+                pay_token_program,
+            )?;
             process_transfer_from_pool(
                 pool_recieve,
                 payer_recieve,
+                receive_mint,
                 receive_amount,
                 self,
-                token_program,
+                receive_token_program,
+            )?;
+            Ok(())
+        }
+    }
+
+    /// 与`process_swap`相反: 用户指定要收到的数额`r`, 程序求出需要支付的`p`
+    ///
+    /// 资产带转账手续费时, `r`为payer实际收到的数额, `p`包含pool收到之前扣除的手续费
+    fn process_swap_exact_out(
+        &mut self,
+        receive: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+            u64,
+        ),
+        pay: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
+        max_amount_in: u64,
+        authority: &Signer<'info>,
+        token_programs: (
+            &Interface<'info, TokenInterface>,
+            &Interface<'info, TokenInterface>,
+        ),
+    ) -> Result<()> {
+        // (From, To)
+        let (receive_mint, pool_recieve, payer_recieve, receive_amount) = receive;
+        let receive_index = self.asset_index(&receive_mint.key())?;
+        // (From, To)
+        let (pay_mint, payer_pay, pool_pay) = pay;
+        let pay_index = self.asset_index(&pay_mint.key())?;
+        let (receive_token_program, pay_token_program) = token_programs;
+
+        // 计算需要支付的数额
+        let pool_receive_amount = receive_amount
+            .checked_add(determine_transfer_fee_inverse(
+                receive_mint,
+                receive_amount,
+            )?)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        let pool_pay_amount = self.determine_pay(
+            (receive_index, receive_mint.decimals),
+            (pay_index, pay_mint.decimals),
+            pool_receive_amount,
+        )?;
+        let pay_amount = pool_pay_amount
+            .checked_add(determine_transfer_fee_inverse(pay_mint, pool_pay_amount)?)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+        // Process the swap
+        if pay_amount > max_amount_in {
+            Err(SwapProgramError::InvalidSwapMaxAmountInExceeded.into())
+        } else {
+            self.record_swap(
+                (pay_index, pool_pay_amount),
+                (receive_index, pool_receive_amount),
+                authority.key(),
+            )?;
+            process_transfer_to_pool(
+                payer_pay,
+                pool_pay,
+                pay_mint,
+                pay_amount,
+                authority,
+                pay_token_program,
+            )?;
+            process_transfer_from_pool(
+                pool_recieve,
+                payer_recieve,
+                receive_mint,
+                pool_receive_amount,
+                self,
+                receive_token_program,
             )?;
             Ok(())
         }
     }
 
+    /// 沿着`path`依次swap: 每一跳支付上一跳收到的数额, 只检查最终收到的数额是否低于`min_amount_out`
+    ///
+    /// 中间资产先从pool转出再转回, 相互抵消, 所以只需要两次转账, 转账手续费也只在第一种和最后一种资产上扣除。
+    /// 每一跳都按上一跳交易后的储备计算
+    fn process_swap_route(
+        &mut self,
+        path: &[(
+            InterfaceAccount<'info, Mint>,
+            InterfaceAccount<'info, TokenAccount>,
+        )],
+        pay: (&InterfaceAccount<'info, TokenAccount>, u64),
+        receive: (&InterfaceAccount<'info, TokenAccount>, u64),
+        authority: &Signer<'info>,
+        token_programs: (&Program<'info, Token>, &Program<'info, Token2022>),
+    ) -> Result<()> {
+        // (From, Amount)
+        let (payer_pay, amount_in) = pay;
+        // (To, Minimum Amount)
+        let (payer_receive, min_amount_out) = receive;
+        // (Mint, From/To)
+        let (pay_mint, pool_pay) = &path[0];
+        let (receive_mint, pool_recieve) = &path[path.len() - 1];
+
+        // 路径上每一种资产在池子中的位置, 储备在每一跳之后更新
+        let indexes = path
+            .iter()
+            .map(|(mint, _)| self.asset_index(&mint.key()))
+            .collect::<Result<Vec<usize>>>()?;
+
+        let mut amount = amount_in - determine_transfer_fee(pay_mint, amount_in)?;
+        for (i, hop) in path.windows(2).enumerate() {
+            let (hop_pay_mint, hop_receive_mint) = (&hop[0].0, &hop[1].0);
+            let (pay_index, receive_index) = (indexes[i], indexes[i + 1]);
+
+            // 计算这一跳接收到的数额
+            let receive_amount = self.determine_receive(
+                (receive_index, hop_receive_mint.decimals),
+                (pay_index, hop_pay_mint.decimals),
+                amount,
+            )?;
+            if receive_amount == 0 {
+                return Err(SwapProgramError::InvalidSwapNotEnoughPay.into());
+            }
+
+            self.record_swap(
+                (pay_index, amount),
+                (receive_index, receive_amount),
+                authority.key(),
+            )?;
+            amount = receive_amount;
+        }
+
+        if amount - determine_transfer_fee(receive_mint, amount)? < min_amount_out {
+            return Err(SwapProgramError::InvalidSwapSlippageExceeded.into());
+        }
+
+        process_transfer_to_pool(
+            payer_pay,
+            pool_pay,
+            pay_mint,
+            amount_in,
+            authority,
+            &token_program_of(pay_mint, token_programs)?,
+        )?;
+        process_transfer_from_pool(
+            pool_recieve,
+            payer_receive,
+            receive_mint,
+            amount,
+            self,
+            &token_program_of(receive_mint, token_programs)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// 记录一笔swap: 累计协议手续费, 更新两种资产的储备和TWAP价格, 并发出`SwapEvent`
+    ///
+    /// `pay`和`receive`为 (资产的位置, 数额)
+    fn record_swap(
+        &mut self,
+        pay: (usize, u64),
+        receive: (usize, u64),
+        user: Pubkey,
+    ) -> Result<()> {
+        let (pay_index, pay_amount) = pay;
+        let (receive_index, receive_amount) = receive;
+
+        // 支付的数额中除了协议手续费都归流动性提供者
+        let protocol_fee = self.accrue_protocol_fee(pay_index, pay_amount)?;
+        self.add_reserve(pay_index, pay_amount - protocol_fee)?;
+        self.sub_reserve(receive_index, receive_amount)?;
+        self.record_price(pay_index)?;
+        self.record_price(receive_index)?;
+
+        emit!(SwapEvent {
+            pool: self.key(),
+            user,
+            pay_mint: self.assets[pay_index],
+            receive_mint: self.assets[receive_index],
+            pay_amount,
+            receive_amount,
+            fee_amount: self.fee_amount(pay_amount),
+            pay_reserve: self.reserve(pay_index),
+            receive_reserve: self.reserve(receive_index),
+        });
+
+        Ok(())
+    }
+
+    /// 将资产从流动性池的铸币地址列表中移除, 池子中该资产属于流动性提供者的储备(`reserve`)必须为0
+    ///
+    /// Token账户中剩下的余额(尚未领取的协议手续费, 以及直接转入、不属于储备的余额)转移到treasury的
+    /// Token账户, 然后关闭pool的Token账户, 并缩小流动性池账户的大小, 多余的租金都退还给`rent_receiver`
+    fn remove_asset(
+        &mut self,
+        delist: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
+        rent_receiver: &Signer<'info>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        // (Mint, pool's Token Account, treasury's Token Account)
+        let (mint, pool_token_account, treasury_token_account) = delist;
+        let index = self.asset_index(&mint.key())?;
+
+        if self.reserve(index) > 0 {
+            return Err(SwapProgramError::InvalidDelistNotEmpty.into());
+        }
+
+        // pool's Token Account => treasury's Token Account
+        if pool_token_account.amount > 0 {
+            process_transfer_from_pool(
+                pool_token_account,
+                treasury_token_account,
+                mint,
+                pool_token_account.amount,
+                self,
+                token_program,
+            )?;
+        }
+
+        self.assets.remove(index);
+        self.weights.remove(index);
+        self.target_weights.remove(index);
+        self.protocol_fees.remove(index);
+        self.reserves.remove(index);
+        self.decimals.remove(index);
+        self.price_accumulators.remove(index);
+        self.shrink(LiquidityPool::ASSET_SPACE, rent_receiver)?;
+
+        process_close_pool_token_account(pool_token_account, rent_receiver, self, token_program)?;
+
+        Ok(())
+    }
+
+    /// 缩小账户的大小, 与`realloc`相反, 将不再需要的租金退还给`rent_receiver`
+    fn shrink(&mut self, space_to_remove: usize, rent_receiver: &Signer<'info>) -> Result<()> {
+        let account_info = self.to_account_info();
+        let new_account_size = account_info.data_len() - space_to_remove;
+
+        // 重新分配account
+        account_info.realloc(new_account_size, false)?;
+
+        // 退还多余的租金Rent
+        let lamports_required = (Rent::get()?).minimum_balance(new_account_size);
+        let excess_rent = account_info.lamports() - lamports_required;
+        **account_info.try_borrow_mut_lamports()? -= excess_rent;
+        **rent_receiver.to_account_info().try_borrow_mut_lamports()? += excess_rent;
+
+        Ok(())
+    }
+
+    /// 将某一种资产累计的协议手续费从池子转移到treasury的Token账户
+    fn collect_protocol_fees(
+        &mut self,
+        collect: (
+            &InterfaceAccount<'info, Mint>,
+            &InterfaceAccount<'info, TokenAccount>,
+            &InterfaceAccount<'info, TokenAccount>,
+        ),
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        // (Mint, From, To)
+        let (mint, pool_token_account, treasury_token_account) = collect;
+        let index = self.asset_index(&mint.key())?;
+
+        let amount = self.protocol_fees[index];
+        if amount > 0 {
+            self.protocol_fees[index] = 0;
+            process_transfer_from_pool(
+                pool_token_account,
+                treasury_token_account,
+                mint,
+                amount,
+                self,
+                token_program,
+            )?;
+        }
+
+        Ok(())
+    }
+
     fn determine_auto_fund_pool_interval(
         &self,
         pool_token_account_balance: u64,
@@ -227,96 +1335,854 @@ impl<'info> LiquidityPoolAccount<'info> for Account<'info, LiquidityPool> {
     }
 }
 
+/// A program-derived pool account that signs for the token accounts it owns
+pub trait PoolSigner {
+    /// The seeds of the pool's address, including its bump
+    fn signer_seeds(&self) -> Vec<Vec<u8>>;
+}
+
+impl PoolSigner for LiquidityPool {
+    fn signer_seeds(&self) -> Vec<Vec<u8>> {
+        vec![
+            LiquidityPool::SEED_PREFIX.as_bytes().to_vec(),
+            self.pool_id.to_le_bytes().to_vec(),
+            vec![self.bump],
+        ]
+    }
+}
+
+/// Wrap native SOL when the payer's token account holds the native mint: move
+/// the lamports it is short of `amount` into the account and sync its balance
+pub(crate) fn process_wrap_sol<'info>(
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+    authority: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    if token_account.mint != native_mint::ID || token_account.amount >= amount {
+        return Ok(());
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: authority.to_account_info(),
+                to: token_account.to_account_info(),
+            },
+        ),
+        amount - token_account.amount,
+    )?;
+    sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        SyncNative {
+            account: token_account.to_account_info(),
+        },
+    ))
+}
+
+/// Unwrap native SOL when the payer's token account holds the native mint:
+/// close the account so its whole balance and rent go back to the payer as
+/// lamports
+///
+/// Only an account that held no wSOL before the instruction (`initial_amount`)
+/// is closed, so exactly what the instruction wrapped or paid out is unwrapped.
+/// wSOL the payer already held stays wrapped, together with whatever the
+/// instruction left in the account
+pub(crate) fn process_unwrap_sol<'info>(
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    initial_amount: u64,
+    authority: &Signer<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    if token_account.mint != native_mint::ID || initial_amount > 0 {
+        return Ok(());
+    }
+
+    close_account(CpiContext::new(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: token_account.to_account_info(),
+            destination: authority.to_account_info(),
+            authority: authority.to_account_info(),
+        },
+    ))
+}
+
+/// Pick the token program that owns `mint` out of the SPL Token and Token-2022
+/// programs, for instructions whose assets are only known at runtime
+pub(crate) fn token_program_of<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    token_programs: (&Program<'info, Token>, &Program<'info, Token2022>),
+) -> Result<AccountInfo<'info>> {
+    let owner = *mint.to_account_info().owner;
+    if owner == token_programs.0.key() {
+        Ok(token_programs.0.to_account_info())
+    } else if owner == token_programs.1.key() {
+        Ok(token_programs.1.to_account_info())
+    } else {
+        Err(SwapProgramError::InvalidTokenProgram.into())
+    }
+}
+
+/// The `TransferFeeConfig` extension of a Token-2022 mint, if it has one
+fn transfer_fee_config(mint: &InterfaceAccount<Mint>) -> Result<Option<TransferFeeConfig>> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != token_2022::ID {
+        return Ok(None);
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(state.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Calculates the transfer fee withheld from a transfer of `amount` of `mint`
+/// in the current epoch, so the receiver gets `amount - fee`
+///
+/// Mints without the transfer-fee extension (including every SPL Token mint)
+/// charge nothing
+pub fn determine_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    match transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(SwapProgramError::InvalidArithmetic.into()),
+        None => Ok(0),
+    }
+}
+
+/// The inverse of `determine_transfer_fee`: the fee to add on top of
+/// `post_fee_amount` so that the receiver gets exactly `post_fee_amount`
+pub fn determine_transfer_fee_inverse(
+    mint: &InterfaceAccount<Mint>,
+    post_fee_amount: u64,
+) -> Result<u64> {
+    match transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
+            .ok_or(SwapProgramError::InvalidArithmetic.into()),
+        None => Ok(0),
+    }
+}
+
 /// Process a transfer from one the payer's token account to the
 /// pool's token account using a CPI
-fn process_transfer_to_pool<'info>(
-    from: &Account<'info, TokenAccount>,
-    to: &Account<'info, TokenAccount>,
+pub(crate) fn process_transfer_to_pool<'info>(
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
     amount: u64,
     authority: &Signer<'info>,
-    token_program: &Program<'info, Token>,
+    token_program: &impl ToAccountInfo<'info>,
 ) -> Result<()> {
-    transfer(
+    transfer_checked(
         CpiContext::new(
             token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: from.to_account_info(),
+                mint: mint.to_account_info(),
                 to: to.to_account_info(),
                 authority: authority.to_account_info(),
             },
         ),
         amount,
+        mint.decimals,
     )
 }
 
 /// Process a transfer from the pool's token account to the
 /// payer's token account using a CPI with signer seeds
-fn process_transfer_from_pool<'info>(
-    from: &Account<'info, TokenAccount>,
-    to: &Account<'info, TokenAccount>,
+pub(crate) fn process_transfer_from_pool<'info, T>(
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    pool: &Account<'info, T>,
+    token_program: &impl ToAccountInfo<'info>,
+) -> Result<()>
+where
+    T: PoolSigner + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let seeds = pool.signer_seeds();
+    let seeds = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            &[&seeds],
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+/// Close one of the pool's token accounts using a CPI with signer seeds,
+/// returning its rent to `destination`
+fn process_close_pool_token_account<'info>(
+    account: &InterfaceAccount<'info, TokenAccount>,
+    destination: &Signer<'info>,
+    pool: &Account<'info, LiquidityPool>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: account.to_account_info(),
+            destination: destination.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        &[&[
+            LiquidityPool::SEED_PREFIX.as_bytes(),
+            pool.pool_id.to_le_bytes().as_ref(),
+            &[pool.bump],
+        ]],
+    ))
+}
+
+/// Mint LP shares to the liquidity provider's token account using a CPI
+/// with the pool's signer seeds
+fn process_mint_lp_shares<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
     amount: u64,
     pool: &Account<'info, LiquidityPool>,
     token_program: &Program<'info, Token>,
 ) -> Result<()> {
-    transfer(
+    mint_to(
         CpiContext::new_with_signer(
             token_program.to_account_info(),
-            Transfer {
-                from: from.to_account_info(),
+            MintTo {
+                mint: mint.to_account_info(),
                 to: to.to_account_info(),
                 authority: pool.to_account_info(),
             },
-            &[&[LiquidityPool::SEED_PREFIX.as_bytes(), &[pool.bump]]],
+            &[&[
+                LiquidityPool::SEED_PREFIX.as_bytes(),
+                pool.pool_id.to_le_bytes().as_ref(),
+                &[pool.bump],
+            ]],
         ),
         amount,
     )
 }
 
-fn determine_swap_receive(
+/// Burn LP shares from the liquidity provider's token account using a CPI
+fn process_burn_lp_shares<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+    authority: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: mint.to_account_info(),
+                from: from.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Calculates how many LP shares a single-asset deposit of `amount` is worth
+///
+/// An asset with normalized weight `w = weight / Σw` holds that share of the
+/// pool's value, so the invariant grows by `(1 + amount / B)^w` when `B` grows
+/// by `amount`, and the depositor receives that growth of the LP supply:
+///
+/// `shares = S * ((1 + amount / B)^w - 1)`
+///
+/// The pool cannot value an asset it holds none of (`B = 0`), so listing
+/// deposits and the first deposit into an empty pool are handled by `fund`
+/// and rejected here. The result is rounded down, in the pool's favour
+fn determine_deposit_shares(
+    lp_supply: u64,
+    pool_balance: u64,
+    amount: u64,
+    weight: u64,
+    other_weights: u64,
+) -> Result<u64> {
+    if lp_supply == 0 || pool_balance == 0 {
+        return Err(SwapProgramError::InvalidLiquidityZeroShares.into());
+    }
+    // (B + amount) / B, in WAD
+    let growth = mul_div_floor(
+        pool_balance as u128 + amount as u128,
+        WAD,
+        pool_balance as u128,
+    )
+    .ok_or(SwapProgramError::InvalidArithmetic)?;
+    // ((B + amount) / B)^w
+    let exponent = mul_div_floor(weight as u128, WAD, weight as u128 + other_weights as u128)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let invariant_growth = pow_down(growth, exponent).ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    let shares = mul_div_floor(lp_supply as u128, invariant_growth.saturating_sub(WAD), WAD)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    u64::try_from(shares).map_err(|_| SwapProgramError::InvalidArithmetic.into())
+}
+
+/// Calculates the part of a single-asset deposit of `amount` that is charged
+/// the swap fee
+///
+/// A proportional deposit would add every asset in the ratio of the reserves;
+/// adding only this asset is equivalent to depositing its own share
+/// `amount * w` and swapping the rest, `amount * (1 - w)`, into the other
+/// assets, where `w = weight / Σw`. Only that implied swap pays the fee, as
+/// in Balancer's single-asset joins. The result is rounded down
+fn determine_deposit_taxable(amount: u64, weight: u64, other_weights: u64) -> Result<u64> {
+    mul_div_floor(
+        amount as u128,
+        other_weights as u128,
+        weight as u128 + other_weights as u128,
+    )
+    .map(|taxable| taxable as u64)
+    .ok_or(SwapProgramError::InvalidArithmetic.into())
+}
+
+/// Calculates how many LP shares a single-asset deposit of `amount` into a
+/// two-asset StableSwap pool is worth, and the part of it charged the fee
+///
+/// The shares follow the growth of the invariant, `shares = S * (D2 - D0) / D0`,
+/// as in Curve's `add_liquidity`. The ideal deposit would grow every balance
+/// by `D1 / D0`, so both balances are charged `fee_bps / 2` (Curve's
+/// `fee * n / (4 * (n - 1))` for `n = 2`) on how far the deposit moves them
+/// away from that, and `D2` is the invariant of the balances left after the
+/// fee. The fee stays in the pool for the liquidity providers.
+///
+/// `deposit` and `other` are (reserve, mint decimals) of the deposited asset
+/// and of the other asset. The returned taxable part is the deposited
+/// asset's distance from its ideal balance, in its own precision. Shares are
+/// rounded down and keep `stable_math::ROUNDING_MARGIN` of `D` in the pool
+fn determine_stable_deposit_shares(
+    lp_supply: u64,
+    deposit: (u64, u8),
+    other: (u64, u8),
+    amount: u64,
+    amp: u64,
+    fee_bps: u16,
+) -> Result<(u64, u64)> {
+    let (deposit_reserve, deposit_decimals) = deposit;
+    let (other_reserve, other_decimals) = other;
+    let (x_scale, y_scale) = stable_swap_scales(deposit_decimals, other_decimals)?;
+    let amp = amp as u128;
+
+    let x0 = deposit_reserve as u128 * x_scale;
+    let y0 = other_reserve as u128 * y_scale;
+    let x1 = (amount as u128)
+        .checked_mul(x_scale)
+        .and_then(|a| a.checked_add(x0))
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    let d0 = stable_math::compute_d(amp, x0, y0).ok_or(SwapProgramError::InvalidArithmetic)?;
+    let d1 = stable_math::compute_d(amp, x1, y0).ok_or(SwapProgramError::InvalidArithmetic)?;
+    if d0 == 0 {
+        return Err(SwapProgramError::InvalidArithmetic.into());
+    }
+
+    // How far each new balance is from the ideal balance `B0 * D1 / D0`
+    let diff_x = mul_div_floor(x0, d1, d0)
+        .map(|ideal| ideal.abs_diff(x1))
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let diff_y = mul_div_floor(y0, d1, d0)
+        .map(|ideal| ideal.abs_diff(y0))
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let fee = |diff: u128| {
+        mul_div_ceil(
+            diff,
+            fee_bps as u128,
+            2 * LiquidityPool::BPS_DENOMINATOR as u128,
+        )
+        .ok_or(SwapProgramError::InvalidArithmetic)
+    };
+    let x2 = x1.saturating_sub(fee(diff_x)?);
+    let y2 = y0.saturating_sub(fee(diff_y)?);
+    let d2 = stable_math::compute_d(amp, x2, y2).ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    let growth = d2
+        .saturating_sub(d0)
+        .saturating_sub(stable_math::ROUNDING_MARGIN);
+    let shares = mul_div_floor(lp_supply as u128, growth, d0)
+        .and_then(|shares| u64::try_from(shares).ok())
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    Ok((shares, (diff_x / x_scale) as u64))
+}
+
+/// Calculates `r = f(p)` for the weighted-product curve with the swap fee
+/// taken out of the pay side:
+///
+/// `r = R * (1 - (P / (P + p * γ))^(w_P / w_R))`, where `γ = 1 - fee_bps / 10_000`
+///
+/// When both assets have the same weight this is the constant-product curve
+///
+/// `r = R * p * γ / (P + p * γ)`
+///
+/// which is computed exactly: both sides of the fraction are scaled by the
+/// basis-point denominator so the whole calculation stays in integers.
+/// Otherwise the power is taken in WAD fixed point and rounded against the
+/// trader. Since `R` and `r` share a mint (as do `P` and `p`), the mint
+/// decimals cancel out and the raw token amounts can be used directly. The
+/// result is rounded down, so any remainder stays in the pool
+pub fn determine_swap_receive(
     pool_recieve_balance: u64,
-    receive_decimals: u8,
+    recieve_weight: u64,
     pool_pay_balance: u64,
-    pay_decimals: u8,
+    pay_weight: u64,
     pay_amount: u64,
+    fee_bps: u16,
 ) -> Result<u64> {
-    // 交易手续费用 1%
-    let t = 0.01;
-    let gamma = 1.00 - t;
-
-    // Convert all values to nominal floats using their respective mint decimal
-    // places
-    let big_r = convert_to_float(pool_recieve_balance, receive_decimals);
-    let big_p = convert_to_float(pool_pay_balance, pay_decimals);
-    let p = convert_to_float(pay_amount, pay_decimals);
+    let big_r = pool_recieve_balance as u128;
+    let big_p = pool_pay_balance as u128;
+    // p * γ, in basis points
+    let p_gamma = (pay_amount as u128)
+        .checked_mul((LiquidityPool::BPS_DENOMINATOR - fee_bps as u64) as u128)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    // P, in basis points
+    let big_p_scaled = big_p
+        .checked_mul(LiquidityPool::BPS_DENOMINATOR as u128)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
     // Calculate `f(p)` to get `r`
-    let numerator = big_r.mul(p).mul(gamma);
-    let denomiator = big_p.add(p.mul(gamma));
-    let r = numerator.div(denomiator);
+    let r = if recieve_weight == pay_weight {
+        let denominator = big_p_scaled
+            .checked_add(p_gamma)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        mul_div_floor(big_r, p_gamma, denominator)
+    } else {
+        weighted_math::out_given_in(
+            big_p_scaled,
+            pay_weight as u128,
+            big_r,
+            recieve_weight as u128,
+            p_gamma,
+        )
+    }
+    .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    // Make sure `r` does not drain the liquidity
+    if r >= big_r {
+        return Err(SwapProgramError::InvalidSwapNotEnoughLiquidity.into());
+    }
+
+    Ok(r as u64)
+}
 
-    // Make sure `r` does not exceed liquidity
-    if r > big_r {
+/// Calculates `p = f⁻¹(r)`, the inverse of `determine_swap_receive`:
+///
+/// `p = P * ((R / (R - r))^(w_R / w_P) - 1) / γ`
+///
+/// which for assets of the same weight is computed exactly as
+///
+/// `p = P * r / ((R - r) * γ)`
+///
+/// The result is rounded up, so the pool is always paid at least enough for
+/// `determine_swap_receive(R, P, p)` to be no less than `r`
+pub fn determine_swap_pay(
+    pool_recieve_balance: u64,
+    recieve_weight: u64,
+    pool_pay_balance: u64,
+    pay_weight: u64,
+    receive_amount: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    // Make sure `r` does not drain the liquidity
+    if receive_amount >= pool_recieve_balance {
         return Err(SwapProgramError::InvalidSwapNotEnoughLiquidity.into());
     }
-    // Return the real value of `r`
-    Ok(convert_from_float(r, receive_decimals))
+
+    let gamma = (LiquidityPool::BPS_DENOMINATOR - fee_bps as u64) as u128;
+
+    // Calculate `f⁻¹(r)` to get `p`
+    let p = if recieve_weight == pay_weight {
+        // (R - r) * γ, in basis points
+        let denominator = ((pool_recieve_balance - receive_amount) as u128)
+            .checked_mul(gamma)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        let r_scaled = (receive_amount as u128)
+            .checked_mul(LiquidityPool::BPS_DENOMINATOR as u128)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        mul_div_ceil(pool_pay_balance as u128, r_scaled, denominator)
+    } else {
+        weighted_math::in_given_out(
+            pool_pay_balance as u128,
+            pay_weight as u128,
+            pool_recieve_balance as u128,
+            recieve_weight as u128,
+            receive_amount as u128,
+        )
+        .and_then(|p| mul_div_ceil(p, LiquidityPool::BPS_DENOMINATOR as u128, gamma))
+    }
+    .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    u64::try_from(p).map_err(|_| SwapProgramError::InvalidArithmetic.into())
+}
+
+/// Scales for bringing the balances of two mints to the precision of the one
+/// with more decimals
+fn stable_swap_scales(recieve_decimals: u8, pay_decimals: u8) -> Result<(u128, u128)> {
+    let decimals = recieve_decimals.max(pay_decimals);
+    let scale = |d: u8| {
+        10u128
+            .checked_pow((decimals - d) as u32)
+            .ok_or(SwapProgramError::InvalidArithmetic)
+    };
+    Ok((scale(recieve_decimals)?, scale(pay_decimals)?))
 }
 
-/// Converts a `u64` value - in this case the balance of a token account - into
-/// an `f32` by using the `decimals` value of its associated mint to get the
-/// nominal quantity of a mint stored in that token account
+/// Calculates `r = f(p)` for the StableSwap curve with the swap fee taken out
+/// of the pay side
 ///
-/// For example, a token account with a balance of 10,500 for a mint with 3
-/// decimals would have a nominal balance of 10.5
-fn convert_to_float(value: u64, decimals: u8) -> f32 {
-    (value as f32).div(f32::powf(10.0, decimals as f32))
+/// Unlike the constant-product curve the invariant is not homogeneous across
+/// mints, so both balances are first brought to the same precision with the
+/// mints' decimals. A StableSwap pool holds at most two assets, so the two
+/// balances of the swap determine the whole invariant `D`; the new receive
+/// balance `y` follows from `P + p * γ` and `D`, and
+/// `r = R - y` less `stable_math::ROUNDING_MARGIN`, rounded down
+pub fn determine_stable_swap_receive(
+    pool_recieve: (u64, u8),
+    pool_pay: (u64, u8),
+    pay_amount: u64,
+    amp: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    let (pool_recieve_balance, recieve_decimals) = pool_recieve;
+    let (pool_pay_balance, pay_decimals) = pool_pay;
+    let (r_scale, p_scale) = stable_swap_scales(recieve_decimals, pay_decimals)?;
+
+    let big_r = (pool_recieve_balance as u128)
+        .checked_mul(r_scale)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let big_p = (pool_pay_balance as u128)
+        .checked_mul(p_scale)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let d = stable_math::compute_d(amp as u128, big_p, big_r)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    // p * γ, in the common precision
+    let p_gamma = (pay_amount as u128)
+        .checked_mul(p_scale)
+        .and_then(|p| {
+            mul_div_floor(
+                p,
+                (LiquidityPool::BPS_DENOMINATOR - fee_bps as u64) as u128,
+                LiquidityPool::BPS_DENOMINATOR as u128,
+            )
+        })
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    // Calculate the new receive balance `y` to get `r`
+    let y = big_p
+        .checked_add(p_gamma)
+        .and_then(|x| stable_math::compute_y(amp as u128, x, d))
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let r = big_r
+        .saturating_sub(y)
+        .saturating_sub(stable_math::ROUNDING_MARGIN)
+        / r_scale;
+
+    // Make sure `r` does not drain the liquidity
+    if r >= pool_recieve_balance as u128 {
+        return Err(SwapProgramError::InvalidSwapNotEnoughLiquidity.into());
+    }
+
+    Ok(r as u64)
 }
 
-/// Converts a nominal value - in this case the calculated value `r` - into a
-/// `u64` by using the `decimals` value of its associated mint to get the real
-/// quantity of the mint that the user will receive
+/// Calculates `p = f⁻¹(r)`, the inverse of `determine_stable_swap_receive`
 ///
-/// For example, if `r` is calculated to be 10.5, the real amount of the asset
-/// to be received by the user is 10,500
-fn convert_from_float(value: f32, decimals: u8) -> u64 {
-    value.mul(f32::powf(10.0, decimals as f32)) as u64
+/// The new pay balance `x` follows from `R - r` and `D`, and
+/// `p = (x - P) / γ`, rounded up. Both balances keep a
+/// `stable_math::ROUNDING_MARGIN`, so `determine_stable_swap_receive` of the
+/// result is never less than `r`
+pub fn determine_stable_swap_pay(
+    pool_recieve: (u64, u8),
+    pool_pay: (u64, u8),
+    receive_amount: u64,
+    amp: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    let (pool_recieve_balance, recieve_decimals) = pool_recieve;
+    let (pool_pay_balance, pay_decimals) = pool_pay;
+
+    // Make sure `r` does not drain the liquidity
+    if receive_amount >= pool_recieve_balance {
+        return Err(SwapProgramError::InvalidSwapNotEnoughLiquidity.into());
+    }
+
+    let (r_scale, p_scale) = stable_swap_scales(recieve_decimals, pay_decimals)?;
+
+    let big_r = (pool_recieve_balance as u128)
+        .checked_mul(r_scale)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let big_p = (pool_pay_balance as u128)
+        .checked_mul(p_scale)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+    let d = stable_math::compute_d(amp as u128, big_p, big_r)
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    // Calculate the new pay balance `x` to get `p`. The new receive balance
+    // keeps a margin for both this and the forward calculation
+    let y = (receive_amount as u128 * r_scale)
+        .checked_add(2 * stable_math::ROUNDING_MARGIN)
+        .and_then(|r| big_r.checked_sub(r))
+        .ok_or(SwapProgramError::InvalidSwapNotEnoughLiquidity)?;
+    let x = stable_math::compute_y(amp as u128, y, d).ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    // (x - P) / γ, back in the pay mint's precision
+    let p = x
+        .saturating_sub(big_p)
+        .checked_add(stable_math::ROUNDING_MARGIN)
+        .and_then(|p| mul_div_ceil(p, 1, p_scale))
+        .and_then(|p| {
+            mul_div_ceil(
+                p,
+                LiquidityPool::BPS_DENOMINATOR as u128,
+                (LiquidityPool::BPS_DENOMINATOR - fee_bps as u64) as u128,
+            )
+        })
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+    u64::try_from(p).map_err(|_| SwapProgramError::InvalidArithmetic.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(initial_amp: u64, target_amp: u64) -> AmpRamp {
+        AmpRamp {
+            initial_amp,
+            target_amp,
+            start_slot: 1_000,
+            end_slot: 2_000,
+        }
+    }
+
+    #[test]
+    fn fixed_amp_never_changes() {
+        let amp = AmpRamp::new(100);
+        for slot in [0, 1, 1_000, u64::MAX] {
+            assert_eq!(amp.current(slot), 100);
+        }
+    }
+
+    #[test]
+    fn amp_ramp_holds_its_endpoints() {
+        let amp = ramp(100, 1_000);
+        assert_eq!(amp.current(0), 100);
+        assert_eq!(amp.current(1_000), 100);
+        assert_eq!(amp.current(2_000), 1_000);
+        assert_eq!(amp.current(u64::MAX), 1_000);
+    }
+
+    #[test]
+    fn amp_ramp_interpolates_linearly_upwards() {
+        let amp = ramp(100, 1_000);
+        assert_eq!(amp.current(1_250), 325);
+        assert_eq!(amp.current(1_500), 550);
+        assert_eq!(amp.current(1_750), 775);
+        // rounded towards the initial amp
+        assert_eq!(amp.current(1_001), 100);
+        assert_eq!(amp.current(1_999), 999);
+    }
+
+    #[test]
+    fn amp_ramp_interpolates_linearly_downwards() {
+        let amp = ramp(1_000, 100);
+        assert_eq!(amp.current(1_250), 775);
+        assert_eq!(amp.current(1_500), 550);
+        assert_eq!(amp.current(1_750), 325);
+        // rounded towards the initial amp
+        assert_eq!(amp.current(1_001), 1_000);
+        assert_eq!(amp.current(1_999), 101);
+    }
+
+    #[test]
+    fn amp_ramp_is_monotonic() {
+        let amp = ramp(LiquidityPool::MIN_AMP, LiquidityPool::MAX_AMP);
+        let mut previous = 0;
+        for slot in (900..=2_100).step_by(7) {
+            let current = amp.current(slot);
+            assert!(current >= previous);
+            previous = current;
+        }
+        assert_eq!(previous, LiquidityPool::MAX_AMP);
+    }
+
+    #[test]
+    fn weights_ramp_from_the_current_weights() {
+        let mut pool = LiquidityPool::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            30,
+            CurveType::ConstantProduct,
+            0,
+            0,
+        );
+        for _ in 0..2 {
+            pool.assets.push(Pubkey::new_unique());
+            pool.weights.push(LiquidityPool::DEFAULT_WEIGHT);
+            pool.target_weights.push(LiquidityPool::DEFAULT_WEIGHT);
+            pool.reserves.push(1_000);
+            pool.price_accumulators.push(PriceAccumulator::default());
+        }
+        let ramp_slots = LiquidityPool::MIN_RAMP_SLOTS;
+
+        // an instant or too short change is rejected
+        assert!(pool.ramp_weights(vec![80, 20], 1_000, 1_000).is_err());
+        assert!(pool
+            .ramp_weights(vec![80, 20], 1_000 + ramp_slots - 1, 1_000)
+            .is_err());
+
+        pool.ramp_weights(vec![80, 20], 1_000 + ramp_slots, 1_000)
+            .unwrap();
+        assert_eq!(pool.weight(0, 1_000), 100);
+        assert_eq!(pool.weight(1, 1_000), 100);
+        assert_eq!(pool.weight(0, 1_000 + ramp_slots / 2), 90);
+        assert_eq!(pool.weight(1, 1_000 + ramp_slots / 2), 60);
+        assert_eq!(pool.weight(0, u64::MAX), 80);
+        assert_eq!(pool.weight(1, u64::MAX), 20);
+
+        // a new ramp halfway through starts from where the old one got to
+        let slot = 1_000 + ramp_slots / 2;
+        pool.ramp_weights(vec![50, 50], slot + ramp_slots, slot)
+            .unwrap();
+        assert_eq!(pool.weights, vec![90, 60]);
+        assert_eq!(pool.weight(0, slot), 90);
+        assert_eq!(pool.weight(1, slot + ramp_slots), 50);
+    }
+
+    #[test]
+    fn flash_loan_fee_rounds_up() {
+        let pool = LiquidityPool::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            30,
+            CurveType::ConstantProduct,
+            0,
+            0,
+        );
+        assert_eq!(pool.flash_loan_fee(0).unwrap(), 0);
+        // 1 * 30 / 10_000 is charged as 1, never as a free loan
+        assert_eq!(pool.flash_loan_fee(1).unwrap(), 1);
+        assert_eq!(pool.flash_loan_fee(10_000).unwrap(), 30);
+        assert_eq!(pool.flash_loan_fee(10_001).unwrap(), 31);
+        assert_eq!(
+            pool.flash_loan_fee(u64::MAX).unwrap(),
+            55_340_232_221_128_655
+        );
+    }
+
+    #[test]
+    fn reserves_track_only_accounted_amounts() {
+        let mut pool = LiquidityPool::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            30,
+            CurveType::ConstantProduct,
+            0,
+            0,
+        );
+        pool.reserves.push(0);
+        pool.add_reserve(0, 1_000).unwrap();
+        pool.sub_reserve(0, 400).unwrap();
+        assert_eq!(pool.reserve(0), 600);
+        // more than the reserve can never be paid out
+        assert!(pool.sub_reserve(0, 601).is_err());
+        assert!(pool.add_reserve(0, u64::MAX).is_err());
+        assert_eq!(pool.reserve(0), 600);
+    }
+
+    #[test]
+    fn deposit_shares_need_a_valued_reserve() {
+        // the first deposit and listings are minted by `fund`, never priced here
+        assert!(determine_deposit_shares(0, 1_000, 1_000, 100, 100).is_err());
+        assert!(determine_deposit_shares(1_000, 0, 1_000, 100, 100).is_err());
+        // doubling the only asset of a pool doubles the supply, rounded down
+        let shares = determine_deposit_shares(1_000_000, 1_000, 1_000, 100, 0).unwrap();
+        assert!((999_999..=1_000_000).contains(&shares));
+    }
+
+    #[test]
+    fn stable_deposit_into_a_balanced_pool_is_nearly_proportional() {
+        let reserve = 1_000_000_000_000;
+        let (shares, taxable) = determine_stable_deposit_shares(
+            2 * reserve,
+            (reserve, 6),
+            (reserve, 6),
+            1_000_000,
+            100,
+            30,
+        )
+        .unwrap();
+        // half of the deposit is the implied swap into the other asset
+        assert!(taxable.abs_diff(500_000) <= 1);
+        // 1_000_000 less 0.15% on both halves, and the rounding margin
+        assert!(shares <= 1_000_000 - 1_500);
+        assert!(shares >= 1_000_000 - 1_510);
+    }
+
+    #[test]
+    fn stable_deposit_into_an_imbalanced_pool_cannot_be_withdrawn_at_a_profit() {
+        // 900_000 of a 6-decimal asset and 100_000 of a 9-decimal asset
+        let assets = [(900_000_000_000_u64, 6_u8), (100_000_000_000_000_u64, 9_u8)];
+        let supply = 1_000_000_000_000_u128;
+        for (deposit, other) in [(0, 1), (1, 0)] {
+            let (reserve, decimals) = assets[deposit];
+            let (other_reserve, other_decimals) = assets[other];
+            for units in [1, 10_000, 1_000_000] {
+                let amount = units * 10_u64.pow(decimals as u32);
+                for amp in [1, 100, 10_000] {
+                    let (shares, _) = determine_stable_deposit_shares(
+                        supply as u64,
+                        assets[deposit],
+                        assets[other],
+                        amount,
+                        amp,
+                        30,
+                    )
+                    .unwrap();
+
+                    // withdraw proportionally right after the deposit
+                    let total = supply + shares as u128;
+                    let reserve = reserve + amount;
+                    let out = (reserve as u128 * shares as u128 / total) as u64;
+                    let other_out = (other_reserve as u128 * shares as u128 / total) as u64;
+
+                    // and swap the other asset back into the deposited one
+                    let back = determine_stable_swap_receive(
+                        (reserve - out, decimals),
+                        (other_reserve - other_out, other_decimals),
+                        other_out,
+                        amp,
+                        30,
+                    )
+                    .unwrap();
+                    assert!(out + back <= amount);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_amp_enforces_the_bounds() {
+        assert!(LiquidityPool::check_amp(LiquidityPool::MIN_AMP).is_ok());
+        assert!(LiquidityPool::check_amp(LiquidityPool::MAX_AMP).is_ok());
+        assert!(LiquidityPool::check_amp(LiquidityPool::MIN_AMP - 1).is_err());
+        assert!(LiquidityPool::check_amp(LiquidityPool::MAX_AMP + 1).is_err());
+    }
 }
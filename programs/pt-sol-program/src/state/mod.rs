@@ -1,5 +1,9 @@
+pub mod concentrated_pool;
 pub mod liq_pool;
+pub mod price_oracle;
 pub mod staking;
 
+pub use concentrated_pool::*;
 pub use liq_pool::*;
+pub use price_oracle::*;
 pub use staking::*;
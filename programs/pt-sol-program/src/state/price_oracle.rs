@@ -0,0 +1,80 @@
+//! TWAP price oracle account state
+use anchor_lang::prelude::*;
+
+/// The `PriceOracle` state - a ring buffer of observations of one asset pair
+/// of a `LiquidityPool`
+///
+/// Every observation stores the pool's cumulative log price of `base_mint` in
+/// `quote_mint` at a slot, so the time-weighted average price over a window is
+/// `exp((cumulative_now - cumulative_then) / (slot_now - slot_then))`
+#[account]
+pub struct PriceOracle {
+    pub pool: Pubkey,
+    /// 被计价的资产
+    pub base_mint: Pubkey,
+    /// 计价的资产
+    pub quote_mint: Pubkey,
+    /// 最新的观测在`observations`中的位置
+    pub observation_index: u16,
+    /// 已经记录的观测数量, 最多为`PriceOracle::SIZE`
+    pub observation_count: u16,
+    pub observations: [Observation; PriceOracle::SIZE],
+    pub bump: u8,
+}
+
+/// The cumulative log price of a pair at `slot`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct Observation {
+    pub slot: u64,
+    /// 累计对数价格, WAD, 溢出时回绕
+    pub price_cumulative: i128,
+}
+
+impl PriceOracle {
+    // PriceOracle的seed prefix, 用于derive PDA
+    pub const SEED_PREFIX: &'static str = "price_oracle";
+
+    // 保存的观测数量
+    pub const SIZE: usize = 64;
+
+    // 两次观测之间至少间隔的slot数, 约为一分钟
+    pub const MIN_OBSERVATION_INTERVAL: u64 = 150;
+
+    // discrimator + Pubkey + Pubkey + Pubkey + u16 + u16 + [Observation; SIZE] + u8
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 2 + 2 + (8 + 16) * Self::SIZE + 1;
+
+    // create a new price oracle state with its first observation
+    pub fn new(pool: Pubkey, mints: (Pubkey, Pubkey), observation: Observation, bump: u8) -> Self {
+        let mut observations = [Observation::default(); Self::SIZE];
+        observations[0] = observation;
+        Self {
+            pool,
+            base_mint: mints.0,
+            quote_mint: mints.1,
+            observation_index: 0,
+            observation_count: 1,
+            observations,
+            bump,
+        }
+    }
+
+    // 记录一次观测, 距离上一次观测不足`MIN_OBSERVATION_INTERVAL`时跳过
+    pub fn record(&mut self, observation: Observation) {
+        let latest = self.observations[self.observation_index as usize];
+        if observation.slot < latest.slot + Self::MIN_OBSERVATION_INTERVAL {
+            return;
+        }
+        self.observation_index = ((self.observation_index as usize + 1) % Self::SIZE) as u16;
+        self.observations[self.observation_index as usize] = observation;
+        self.observation_count = (self.observation_count + 1).min(Self::SIZE as u16);
+    }
+
+    // 不晚于`slot`的最新一次观测
+    pub fn observation_before(&self, slot: u64) -> Option<Observation> {
+        (0..self.observation_count as usize)
+            .map(|i| {
+                self.observations[(self.observation_index as usize + Self::SIZE - i) % Self::SIZE]
+            })
+            .find(|observation| observation.slot <= slot)
+    }
+}
@@ -1,13 +1,480 @@
 use anchor_lang::prelude::*;
 
+use crate::error::{StakingError, SwapProgramError};
+use crate::math::mul_div_floor;
+
+/// The `StakingPool` state - the global staking configuration
+///
+/// Stakers deposit `staking_mint` and are paid in `reward_mint` from `vault`,
+/// which emits `reward_rate` every slot shared by all stakers pro-rata to
+/// their weight - the staked amount boosted by the multiplier of their lockup.
+/// `reward_per_token` accumulates the reward emitted per unit of weight since
+/// staking was initialized, so a staker's reward is their weight times the
+/// growth of the accumulator since they were last settled (MasterChef-style)
+///
+/// Lockups end on an epoch boundary. The boost of every lockup ending in an
+/// epoch is tracked in `expiring_weights` and leaves `total_weight` as soon as
+/// `update` crosses that boundary, where `reward_per_token` is checkpointed so
+/// the boost of a stake settled later is only paid up to the end of its lockup
+#[account]
+pub struct StakingPool {
+    /// 可以调整`reward_rate`的管理员
+    pub authority: Pubkey,
+    /// stake的铸币
+    pub staking_mint: Pubkey,
+    /// 奖励的铸币
+    pub reward_mint: Pubkey,
+    /// 存放奖励的vault账户
+    pub vault: Pubkey,
+    /// stake对应的流动性池子, 池子暂停时不能stake、unstake和领取奖励
+    pub pool: Pubkey,
+    /// 每个slot发放的奖励, 以`reward_mint`的最小单位表示
+    pub reward_rate: u64,
+    /// 所有用户stake的总数额
+    pub total_staked: u64,
+    /// 所有用户按锁仓倍数加权之后的总数额
+    pub total_weight: u64,
+    /// `total_weight`中还没有到期的锁仓按倍数多出的部分
+    pub total_boost: u64,
+    /// 每单位权重累计的奖励, 放大`StakingPool::PRECISION`倍
+    pub reward_per_token: u128,
+    /// 上一次更新`reward_per_token`的slot
+    pub last_update_slot: u64,
+    /// 按epoch循环使用, 在该epoch开始时到期的锁仓按倍数多出的权重
+    pub expiring_weights: Vec<u64>,
+    /// 按epoch循环使用, 有锁仓到期的epoch开始时的`reward_per_token`
+    pub checkpoints: Vec<Checkpoint>,
+    pub bump: u8,
+}
+
+/// `reward_per_token` at the start of `epoch`, where some lockups ended
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct Checkpoint {
+    pub epoch: u64,
+    pub reward_per_token: u128,
+}
+
+impl StakingPool {
+    // StakingPool的seed prefix, 用于derive PDA
+    pub const SEED_PREFIX: &'static str = "staking_pool";
+
+    // `reward_per_token`的精度
+    pub const PRECISION: u128 = 1_000_000_000_000;
+
+    // 锁仓结束时间的粒度, 约1天
+    pub const EPOCH_SLOTS: u64 = 216_000;
+
+    // `expiring_weights`和`checkpoints`的长度, 必须大于最长锁仓的epoch数加1
+    pub const EPOCHS: usize = 192;
+
+    // discrimator + Pubkey + Pubkey + Pubkey + Pubkey + Pubkey + u64 + u64 + u64 + u64 + u128
+    // + u64 + Vec<u64> + Vec<Checkpoint> + u8
+    pub const SPACE: usize = 8
+        + 32
+        + 32
+        + 32
+        + 32
+        + 32
+        + 8
+        + 8
+        + 8
+        + 8
+        + 16
+        + 8
+        + (4 + 8 * Self::EPOCHS)
+        + (4 + 24 * Self::EPOCHS)
+        + 1;
+
+    // 奖励倍数的分母, 倍数以基点表示
+    pub const BPS_DENOMINATOR: u16 = 10_000;
+
+    // 可选的锁仓: (锁仓的slot数, 奖励倍数), 每天约216,000个slot
+    // 不锁仓、30天、90天和180天
+    pub const LOCKUPS: [(u64, u16); 4] = [
+        (0, 10_000),
+        (30 * 216_000, 12_500),
+        (90 * 216_000, 15_000),
+        (180 * 216_000, 20_000),
+    ];
+
+    // create a new staking pool of `pool` emitting `reward_rate` per slot from `slot`
+    pub fn new(
+        authority: Pubkey,
+        mints: (Pubkey, Pubkey),
+        accounts: (Pubkey, Pubkey),
+        reward_rate: u64,
+        slot: u64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            authority,
+            staking_mint: mints.0,
+            reward_mint: mints.1,
+            vault: accounts.0,
+            pool: accounts.1,
+            reward_rate,
+            total_staked: 0,
+            total_weight: 0,
+            total_boost: 0,
+            reward_per_token: 0,
+            last_update_slot: slot,
+            expiring_weights: vec![0; Self::EPOCHS],
+            checkpoints: vec![Checkpoint::default(); Self::EPOCHS],
+            bump,
+        }
+    }
+
+    // 编号为`index`的锁仓选项
+    pub fn lockup(index: u8) -> Result<(u64, u16)> {
+        Self::LOCKUPS
+            .get(index as usize)
+            .copied()
+            .ok_or(StakingError::InvalidLockup.into())
+    }
+
+    // 在`slot`锁仓`lock_slots`的结束slot, 向上取整到epoch的开始
+    pub fn lock_end_slot(slot: u64, lock_slots: u64) -> Result<u64> {
+        if lock_slots == 0 {
+            return Ok(slot);
+        }
+        slot.checked_add(lock_slots)
+            .and_then(|end| end.checked_add(Self::EPOCH_SLOTS - 1))
+            .map(|end| end / Self::EPOCH_SLOTS * Self::EPOCH_SLOTS)
+            .ok_or(SwapProgramError::InvalidArithmetic.into())
+    }
+
+    fn epoch_index(epoch: u64) -> usize {
+        (epoch % Self::EPOCHS as u64) as usize
+    }
+
+    // 把上一次更新之后发放的奖励按`total_weight`分摊到`reward_per_token`中,
+    // 依次经过每个有锁仓到期的epoch, 记录检查点并把到期的倍数从`total_weight`中去掉
+    pub fn update(&mut self, slot: u64) -> Result<()> {
+        while self.total_boost > 0 {
+            let epoch = self.last_update_slot / Self::EPOCH_SLOTS + 1;
+            let boundary = epoch * Self::EPOCH_SLOTS;
+            if boundary > slot {
+                break;
+            }
+            self.accrue(boundary)?;
+
+            let index = Self::epoch_index(epoch);
+            let expiring = std::mem::take(&mut self.expiring_weights[index]);
+            self.total_weight = self
+                .total_weight
+                .checked_sub(expiring)
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+            self.total_boost = self
+                .total_boost
+                .checked_sub(expiring)
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+            if expiring > 0 {
+                self.checkpoints[index] = Checkpoint {
+                    epoch,
+                    reward_per_token: self.reward_per_token,
+                };
+            }
+        }
+        self.accrue(slot)
+    }
+
+    // 没有人stake时发放的奖励不计入
+    fn accrue(&mut self, slot: u64) -> Result<()> {
+        if slot <= self.last_update_slot {
+            return Ok(());
+        }
+        if self.total_weight > 0 {
+            let emitted = (slot - self.last_update_slot) as u128 * self.reward_rate as u128;
+            let growth = mul_div_floor(emitted, Self::PRECISION, self.total_weight as u128)
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+            self.reward_per_token = self
+                .reward_per_token
+                .checked_add(growth)
+                .ok_or(SwapProgramError::InvalidArithmetic)?;
+        }
+        self.last_update_slot = slot;
+        Ok(())
+    }
+
+    // 在`lock_end_slot`结束的锁仓到期时的`reward_per_token`,
+    // 检查点已经被之后的epoch覆盖时返回`None`
+    fn checkpoint(&self, lock_end_slot: u64) -> Option<u128> {
+        let epoch = lock_end_slot / Self::EPOCH_SLOTS;
+        let checkpoint = self.checkpoints[Self::epoch_index(epoch)];
+        (checkpoint.epoch == epoch).then_some(checkpoint.reward_per_token)
+    }
+
+    // `weight`在`reward_per_token`从`from`增长到`to`期间累计的奖励, 向下取整
+    fn accrued_reward(weight: u64, from: u128, to: u128) -> Result<u64> {
+        mul_div_floor(weight as u128, to.saturating_sub(from), Self::PRECISION)
+            .and_then(|reward| u64::try_from(reward).ok())
+            .ok_or(SwapProgramError::InvalidArithmetic.into())
+    }
+
+    // 把`stake_info`上一次结算之后应得的奖励计入`reward_owed`, 调用前需要先`update`
+    // 锁仓到期之后倍数恢复为1: 按倍数的奖励只算到到期的检查点, 之后按stake的数额,
+    // 检查点已经被覆盖时(到期之后很久没有结算)整段都按stake的数额
+    pub fn settle(&self, stake_info: &mut StakeInfo, slot: u64) -> Result<()> {
+        let paid = stake_info.reward_per_token_paid;
+        let pending = if stake_info.is_locked(slot) || stake_info.weight == stake_info.amount {
+            Self::accrued_reward(stake_info.weight, paid, self.reward_per_token)?
+        } else {
+            match self.checkpoint(stake_info.lock_end_slot) {
+                Some(checkpoint) => Self::accrued_reward(stake_info.weight, paid, checkpoint)?
+                    .checked_add(Self::accrued_reward(
+                        stake_info.amount,
+                        checkpoint,
+                        self.reward_per_token,
+                    )?)
+                    .ok_or(SwapProgramError::InvalidArithmetic)?,
+                None => Self::accrued_reward(stake_info.amount, paid, self.reward_per_token)?,
+            }
+        };
+        stake_info.reward_owed = stake_info
+            .reward_owed
+            .checked_add(pending)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        stake_info.reward_per_token_paid = self.reward_per_token;
+
+        // 到期的倍数已经在`update`时从`total_weight`中去掉
+        if !stake_info.is_locked(slot) {
+            stake_info.multiplier_bps = Self::BPS_DENOMINATOR;
+            stake_info.weight = stake_info.amount;
+        }
+        Ok(())
+    }
+
+    // 在修改`amount`或锁仓之前, 把`stake_info`的权重从`total_weight`中去掉, 调用前需要先`settle`
+    pub fn remove_weight(&mut self, stake_info: &StakeInfo) -> Result<()> {
+        let boost = stake_info.weight.saturating_sub(stake_info.amount);
+        self.total_weight = self
+            .total_weight
+            .checked_sub(stake_info.weight)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        self.add_boost(stake_info.lock_end_slot, boost, false)
+    }
+
+    // 按新的`amount`和锁仓重新计算`stake_info`的权重并加入`total_weight`,
+    // 按倍数多出的部分在锁仓结束的epoch到期
+    pub fn add_weight(&mut self, stake_info: &mut StakeInfo) -> Result<()> {
+        let weight = mul_div_floor(
+            stake_info.amount as u128,
+            stake_info.multiplier_bps as u128,
+            Self::BPS_DENOMINATOR as u128,
+        )
+        .and_then(|weight| u64::try_from(weight).ok())
+        .ok_or(SwapProgramError::InvalidArithmetic)?;
+
+        self.total_weight = self
+            .total_weight
+            .checked_add(weight)
+            .ok_or(SwapProgramError::InvalidArithmetic)?;
+        stake_info.weight = weight;
+        self.add_boost(stake_info.lock_end_slot, weight - stake_info.amount, true)
+    }
+
+    // 增加或减少在`lock_end_slot`到期的倍数
+    fn add_boost(&mut self, lock_end_slot: u64, boost: u64, add: bool) -> Result<()> {
+        if boost == 0 {
+            return Ok(());
+        }
+        let index = Self::epoch_index(lock_end_slot / Self::EPOCH_SLOTS);
+        let (expiring, total) = if add {
+            (
+                self.expiring_weights[index].checked_add(boost),
+                self.total_boost.checked_add(boost),
+            )
+        } else {
+            (
+                self.expiring_weights[index].checked_sub(boost),
+                self.total_boost.checked_sub(boost),
+            )
+        };
+        self.expiring_weights[index] = expiring.ok_or(SwapProgramError::InvalidArithmetic)?;
+        self.total_boost = total.ok_or(SwapProgramError::InvalidArithmetic)?;
+        Ok(())
+    }
+}
+
 #[account]
 pub struct StakeInfo {
     pub stake_at_slot: u64,
-    pub is_staked: bool,
+    /// stake的数额, 为0时表示没有stake
+    pub amount: u64,
+    /// 锁仓结束的slot, 之前不能unstake
+    pub lock_end_slot: u64,
+    /// 锁仓的奖励倍数, 以基点表示
+    pub multiplier_bps: u16,
+    /// 上一次结算时按倍数加权之后的数额
+    pub weight: u64,
+    /// 上一次结算时的`reward_per_token`
+    pub reward_per_token_paid: u128,
+    /// 已经结算但还没有转出的奖励
+    pub reward_owed: u64,
 }
 
 impl StakeInfo {
     pub fn get_thread_id(key: Pubkey) -> Vec<u8> {
         key.to_bytes()[..16].to_vec()
     }
+
+    // 是否还在锁仓期内
+    pub fn is_locked(&self, slot: u64) -> bool {
+        self.lock_end_slot > slot
+    }
+
+    // 锁仓到`lock_end_slot`, 奖励倍数为`multiplier_bps`
+    // 整个stake共用一个锁仓, 还在锁仓期内时追加的stake必须选择不短于现有锁仓的选项,
+    // 否则短锁仓的追加会按长锁仓的倍数得到奖励
+    pub fn lock(&mut self, lock_end_slot: u64, multiplier_bps: u16, slot: u64) -> Result<()> {
+        if self.is_locked(slot)
+            && (multiplier_bps < self.multiplier_bps || lock_end_slot < self.lock_end_slot)
+        {
+            return Err(StakingError::ShorterLockup.into());
+        }
+        self.lock_end_slot = lock_end_slot;
+        self.multiplier_bps = multiplier_bps;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake_info() -> StakeInfo {
+        StakeInfo {
+            stake_at_slot: 0,
+            amount: 0,
+            lock_end_slot: 0,
+            multiplier_bps: StakingPool::BPS_DENOMINATOR,
+            weight: 0,
+            reward_per_token_paid: 0,
+            reward_owed: 0,
+        }
+    }
+
+    // 与`stake`指令相同的顺序追加stake
+    fn stake(pool: &mut StakingPool, info: &mut StakeInfo, amount: u64, lockup: u8, slot: u64) {
+        let (lock_slots, multiplier_bps) = StakingPool::lockup(lockup).unwrap();
+        pool.update(slot).unwrap();
+        pool.settle(info, slot).unwrap();
+        pool.remove_weight(info).unwrap();
+        info.amount += amount;
+        let lock_end_slot = StakingPool::lock_end_slot(slot, lock_slots).unwrap();
+        info.lock(lock_end_slot, multiplier_bps, slot).unwrap();
+        pool.add_weight(info).unwrap();
+    }
+
+    #[test]
+    fn lock_end_rounds_up_to_an_epoch() {
+        let epoch = StakingPool::EPOCH_SLOTS;
+        assert_eq!(StakingPool::lock_end_slot(100, 0).unwrap(), 100);
+        assert_eq!(
+            StakingPool::lock_end_slot(0, 30 * epoch).unwrap(),
+            30 * epoch
+        );
+        assert_eq!(
+            StakingPool::lock_end_slot(1, 30 * epoch).unwrap(),
+            31 * epoch
+        );
+    }
+
+    #[test]
+    fn settle_resets_the_multiplier_after_the_lockup() {
+        let epoch = StakingPool::EPOCH_SLOTS;
+        let mut pool = StakingPool::new(
+            Pubkey::default(),
+            (Pubkey::default(), Pubkey::default()),
+            (Pubkey::default(), Pubkey::default()),
+            10,
+            0,
+            0,
+        );
+        // 锁仓30天(1.25倍)和不锁仓的两个stake
+        let mut locked = stake_info();
+        let mut unlocked = stake_info();
+        stake(&mut pool, &mut locked, 1_000, 1, 0);
+        stake(&mut pool, &mut unlocked, 1_000, 0, 0);
+        assert_eq!(locked.lock_end_slot, 30 * epoch);
+        assert_eq!((locked.weight, pool.total_weight), (1_250, 2_250));
+
+        // 到期之前结算保持倍数, 按1_250 / 2_250分得奖励
+        pool.update(10 * epoch).unwrap();
+        pool.settle(&mut locked, 10 * epoch).unwrap();
+        assert_eq!(locked.weight, 1_250);
+        assert_eq!(locked.reward_owed, 12_000_000);
+
+        // 到期之后没有人结算, 倍数也已经从`total_weight`中去掉
+        pool.update(40 * epoch).unwrap();
+        assert_eq!((pool.total_weight, pool.total_boost), (2_000, 0));
+
+        // 到期之前的20天按1_250 / 2_250, 之后的10天按1_000 / 2_000
+        pool.settle(&mut locked, 40 * epoch).unwrap();
+        assert_eq!(locked.multiplier_bps, StakingPool::BPS_DENOMINATOR);
+        assert_eq!(locked.weight, 1_000);
+        assert_eq!(locked.reward_owed, 12_000_000 + 24_000_000 + 10_800_000);
+
+        // 另一个stake在前30天按1_000 / 2_250, 之后按1_000 / 2_000
+        pool.settle(&mut unlocked, 40 * epoch).unwrap();
+        assert_eq!(unlocked.reward_owed, 28_800_000 + 10_800_000);
+        assert_eq!(
+            locked.reward_owed + unlocked.reward_owed,
+            40 * epoch * pool.reward_rate
+        );
+    }
+
+    #[test]
+    fn settle_long_after_the_checkpoint_pays_the_base_weight() {
+        let epoch = StakingPool::EPOCH_SLOTS;
+        let mut pool = StakingPool::new(
+            Pubkey::default(),
+            (Pubkey::default(), Pubkey::default()),
+            (Pubkey::default(), Pubkey::default()),
+            10,
+            0,
+            0,
+        );
+        let mut first = stake_info();
+        let mut second = stake_info();
+        stake(&mut pool, &mut first, 1_000, 1, 0);
+        // 第二个锁仓在`EPOCHS`个epoch之后到期, 覆盖第一个锁仓到期时的检查点
+        let slot = StakingPool::EPOCHS as u64 * epoch;
+        stake(&mut pool, &mut second, 1_000, 1, slot);
+        pool.update(slot + 30 * epoch).unwrap();
+
+        // 整段都按1_000计算: 前30天独占, 之后单独的1_000, 最后30天按1_000 / 2_250
+        pool.settle(&mut first, slot + 30 * epoch).unwrap();
+        assert_eq!(first.weight, 1_000);
+        assert_eq!(first.reward_owed, 51_840_000 + 349_920_000 + 28_800_000);
+        assert_eq!(pool.total_weight, 2_000);
+    }
+
+    #[test]
+    fn lock_sets_the_lockup_when_unlocked() {
+        let mut info = stake_info();
+        info.lock(1_000, 20_000, 0).unwrap();
+        assert_eq!((info.lock_end_slot, info.multiplier_bps), (1_000, 20_000));
+
+        // 到期之后可以选择更短的锁仓
+        info.lock(1_100, 10_000, 1_000).unwrap();
+        assert_eq!((info.lock_end_slot, info.multiplier_bps), (1_100, 10_000));
+    }
+
+    #[test]
+    fn locked_top_up_rejects_a_shorter_lockup() {
+        let mut info = stake_info();
+        info.lock(1_000, 20_000, 0).unwrap();
+        assert!(info.lock(500, 10_000, 400).is_err());
+        assert!(info.lock(2_000, 12_500, 400).is_err());
+        assert_eq!((info.lock_end_slot, info.multiplier_bps), (1_000, 20_000));
+    }
+
+    #[test]
+    fn locked_top_up_extends_with_a_longer_lockup() {
+        let mut info = stake_info();
+        info.lock(1_000, 12_500, 0).unwrap();
+        info.lock(2_000, 20_000, 400).unwrap();
+        assert_eq!((info.lock_end_slot, info.multiplier_bps), (2_000, 20_000));
+    }
 }
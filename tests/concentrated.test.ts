@@ -0,0 +1,208 @@
+import * as anchor from "@coral-xyz/anchor";
+import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
+import type { PtSolProgram } from "../target/types/pt_sol_program";
+import {
+  collectFees,
+  createConcentratedPool,
+  getConcentratedPoolAddress,
+  getPositionAddress,
+  getTickArrayAddress,
+  increaseLiquidity,
+  initializeTickArray,
+  openPosition,
+  swapConcentrated,
+  TICK_ARRAY_SIZE,
+} from "./instructions/concentrated";
+import { initToken, mintTokens } from "./instructions/tokens";
+import { toBigIntQuantity } from "./utils/token";
+
+// Metaplex Constants
+const METADATA_SEED = "metadata";
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+// `init_token`铸造的token都是9位小数
+const DECIMALS = 9;
+
+const TICK_SPACING = 10;
+const FEE_BPS = 30;
+
+// 初始价格为1, 即tick 0
+const SQRT_PRICE_ONE_X64 = 1n << 64n;
+
+// 两个仓位: 较宽的仓位覆盖整个swap, 较窄的仓位在swap中途被穿过
+const WIDE = { lower: -200, upper: 200, liquidity: 1_000_000_000_000n };
+const NARROW = { lower: -20, upper: 20, liquidity: 1_000_000_000_000n };
+
+describe("ConcentratedPool", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const wallet = provider.wallet as anchor.Wallet;
+  const payer = wallet.payer;
+  const program = anchor.workspace.PtSolProgram as anchor.Program<PtSolProgram>;
+  const connection = provider.connection;
+
+  // 每次运行使用新的池子
+  const poolId = new Date().getTime();
+  const poolAddress = getConcentratedPoolAddress(program, poolId);
+
+  const mintA = Keypair.generate();
+  const mintB = Keypair.generate();
+
+  // 覆盖[-320, 320)的两个tick数组, 第一个包含当前tick
+  const span = TICK_SPACING * TICK_ARRAY_SIZE;
+  const tickArrayStarts = [0, -span];
+
+  // payer持有的`mint`的余额
+  async function getBalance(mint: PublicKey): Promise<bigint> {
+    const tokenAccount = getAssociatedTokenAddressSync(mint, payer.publicKey);
+    return (await getAccount(connection, tokenAccount)).amount;
+  }
+
+  async function fetchPosition(range: { lower: number; upper: number }) {
+    return program.account.position.fetch(
+      getPositionAddress(program, poolAddress, payer.publicKey, range.lower, range.upper)
+    );
+  }
+
+  it("Create a pool with its tick arrays", async () => {
+    for (const [mintKeypair, symbol] of [
+      [mintA, "CLA"],
+      [mintB, "CLB"],
+    ] as [Keypair, string][]) {
+      const [metadataAddress] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from(METADATA_SEED),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          mintKeypair.publicKey.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      const metadata = {
+        name: `Concentrated ${symbol}`,
+        symbol,
+        uri: "https://arweave.net/NmBFKuCWk4yfAB4a6EGwXlWM92q5B9nBZMlRhpVA4y4",
+      };
+      await initToken(program, wallet, mintKeypair.publicKey, mintKeypair.secretKey, metadata, metadataAddress);
+      await mintTokens(program, wallet, mintKeypair.publicKey, new anchor.BN(1000));
+    }
+
+    await createConcentratedPool(
+      program,
+      payer,
+      poolId,
+      mintA.publicKey,
+      mintB.publicKey,
+      TICK_SPACING,
+      FEE_BPS,
+      SQRT_PRICE_ONE_X64
+    );
+    for (const start of tickArrayStarts) {
+      await initializeTickArray(program, payer, poolAddress, start);
+    }
+
+    const pool = await program.account.concentratedPool.fetch(poolAddress);
+    assert.equal(pool.tickCurrent, 0);
+    assert.equal(pool.liquidity.toString(), "0");
+  });
+
+  it("Open positions and add liquidity", async () => {
+    for (const range of [WIDE, NARROW]) {
+      const balanceA = await getBalance(mintA.publicKey);
+      const balanceB = await getBalance(mintB.publicKey);
+
+      await openPosition(program, payer, poolAddress, range.lower, range.upper);
+      await increaseLiquidity(
+        program,
+        payer,
+        poolAddress,
+        mintA.publicKey,
+        mintB.publicKey,
+        TICK_SPACING,
+        range.lower,
+        range.upper,
+        range.liquidity,
+        toBigIntQuantity(100, DECIMALS),
+        toBigIntQuantity(100, DECIMALS)
+      );
+
+      // 价格在仓位范围内, 两种资产都需要存入
+      const position = await fetchPosition(range);
+      assert.equal(position.liquidity.toString(), range.liquidity.toString());
+      assert.isTrue((await getBalance(mintA.publicKey)) < balanceA);
+      assert.isTrue((await getBalance(mintB.publicKey)) < balanceB);
+    }
+
+    // 两个仓位都包含当前价格
+    const pool = await program.account.concentratedPool.fetch(poolAddress);
+    assert.equal(pool.liquidity.toString(), (WIDE.liquidity + NARROW.liquidity).toString());
+  });
+
+  it("Swap across the lower tick of the narrow position", async () => {
+    const amountIn = toBigIntQuantity(5, DECIMALS);
+    const balanceA = await getBalance(mintA.publicKey);
+    const balanceB = await getBalance(mintB.publicKey);
+
+    // A换B, 价格下降, 按顺序经过两个tick数组
+    await swapConcentrated(
+      program,
+      payer,
+      poolAddress,
+      mintA.publicKey,
+      mintB.publicKey,
+      amountIn,
+      1n,
+      true,
+      tickArrayStarts.map((start) => getTickArrayAddress(program, poolAddress, start))
+    );
+
+    assert.equal(balanceA - (await getBalance(mintA.publicKey)), amountIn);
+    assert.isTrue((await getBalance(mintB.publicKey)) > balanceB);
+
+    // 穿过tick -20之后只剩下较宽仓位的流动性
+    const pool = await program.account.concentratedPool.fetch(poolAddress);
+    assert.isBelow(pool.tickCurrent, NARROW.lower);
+    assert.isAtLeast(pool.tickCurrent, WIDE.lower);
+    assert.equal(pool.liquidity.toString(), WIDE.liquidity.toString());
+  });
+
+  it("Collect fees earned while in range", async () => {
+    const collected: bigint[] = [];
+    for (const range of [WIDE, NARROW]) {
+      const balanceA = await getBalance(mintA.publicKey);
+      const balanceB = await getBalance(mintB.publicKey);
+
+      await collectFees(
+        program,
+        payer,
+        poolAddress,
+        mintA.publicKey,
+        mintB.publicKey,
+        TICK_SPACING,
+        range.lower,
+        range.upper
+      );
+
+      // 只有A换B, 手续费都是资产A
+      collected.push((await getBalance(mintA.publicKey)) - balanceA);
+      assert.equal(await getBalance(mintB.publicKey), balanceB);
+
+      const position = await fetchPosition(range);
+      assert.equal(position.feesOwedA.toString(), "0");
+      assert.equal(position.feesOwedB.toString(), "0");
+    }
+
+    // 较窄的仓位只在穿过tick -20之前分得手续费
+    const [wideFees, narrowFees] = collected;
+    assert.isTrue(narrowFees > 0n);
+    assert.isTrue(wideFees > narrowFees);
+
+    // 领取的手续费不超过swap支付的手续费, 协议手续费为0
+    // swap分为穿过tick -20前后两步, 每一步的手续费向上取整, 最多多收1个最小单位
+    const totalFee = (toBigIntQuantity(5, DECIMALS) * BigInt(FEE_BPS)) / 10_000n;
+    assert.isTrue(wideFees + narrowFees <= totalFee + 2n);
+  });
+});
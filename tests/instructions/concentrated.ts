@@ -0,0 +1,609 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { PtSolProgram } from "../../target/types/pt_sol_program";
+import { getTokenProgramId } from "../utils/token";
+
+/** Number of ticks stored in one `TickArray` account */
+export const TICK_ARRAY_SIZE = 32;
+
+function i32ToBuffer(value: number): Buffer {
+  const buffer = Buffer.alloc(4);
+  buffer.writeInt32LE(value);
+  return buffer;
+}
+
+/**
+ *
+ * Derives the address of a Concentrated Liquidity Pool
+ *
+ * @param program The PtSol program as an `anchor.Program<PtSolProgram>`
+ * @param poolId The id the pool address is derived from
+ */
+export function getConcentratedPoolAddress(
+  program: anchor.Program<PtSolProgram>,
+  poolId: number
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("concentrated_pool"),
+      new anchor.BN(poolId).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+}
+
+/**
+ *
+ * Returns the start tick of the `TickArray` containing `tick`
+ *
+ * @param tick Any tick index
+ * @param tickSpacing The tick spacing of the pool
+ */
+export function getTickArrayStart(tick: number, tickSpacing: number): number {
+  const span = tickSpacing * TICK_ARRAY_SIZE;
+  return Math.floor(tick / span) * span;
+}
+
+/**
+ *
+ * Derives the address of the `TickArray` starting at `startTickIndex`
+ *
+ * @param program The PtSol program as an `anchor.Program<PtSolProgram>`
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param startTickIndex The first tick stored in the array
+ */
+export function getTickArrayAddress(
+  program: anchor.Program<PtSolProgram>,
+  pool: PublicKey,
+  startTickIndex: number
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("tick_array"), pool.toBuffer(), i32ToBuffer(startTickIndex)],
+    program.programId
+  )[0];
+}
+
+/**
+ *
+ * Derives the address of a liquidity position
+ *
+ * @param program The PtSol program as an `anchor.Program<PtSolProgram>`
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param owner The owner of the position
+ * @param tickLower The lower tick of the position
+ * @param tickUpper The upper tick of the position
+ */
+export function getPositionAddress(
+  program: anchor.Program<PtSolProgram>,
+  pool: PublicKey,
+  owner: PublicKey,
+  tickLower: number,
+  tickUpper: number
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("position"),
+      pool.toBuffer(),
+      owner.toBuffer(),
+      i32ToBuffer(tickLower),
+      i32ToBuffer(tickUpper),
+    ],
+    program.programId
+  )[0];
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `create_concentrated_pool` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The creator of the pool
+ * @param poolId The id the pool address is derived from
+ * @param mintA The mint address of asset A
+ * @param mintB The mint address of asset B
+ * @param tickSpacing The spacing between usable ticks
+ * @param feeBps The swap fee of the pool in basis points
+ * @param sqrtPriceX64 The square root of the initial price (amount of B per A) as a Q64.64 number
+ */
+export async function createConcentratedPool(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  poolId: number,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  tickSpacing: number,
+  feeBps: number,
+  sqrtPriceX64: bigint
+) {
+  const pool = getConcentratedPoolAddress(program, poolId);
+  const tokenProgramA = await getTokenProgramId(program.provider.connection, mintA);
+  const tokenProgramB = await getTokenProgramId(program.provider.connection, mintB);
+  return await program.methods
+    .createConcentratedPool(
+      new anchor.BN(poolId),
+      tickSpacing,
+      feeBps,
+      new anchor.BN(sqrtPriceX64.toString())
+    )
+    .accounts({
+      pool,
+      mintA,
+      mintB,
+      poolTokenAccountA: getAssociatedTokenAddressSync(mintA, pool, true, tokenProgramA),
+      poolTokenAccountB: getAssociatedTokenAddressSync(mintB, pool, true, tokenProgramB),
+      payer: payer.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgramA,
+      tokenProgramB,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `initialize_tick_array` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The rent payer
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param startTickIndex The first tick stored in the array, see `getTickArrayStart`
+ */
+export async function initializeTickArray(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  startTickIndex: number
+) {
+  return await program.methods
+    .initializeTickArray(startTickIndex)
+    .accounts({
+      pool,
+      tickArray: getTickArrayAddress(program, pool, startTickIndex),
+      payer: payer.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `open_position` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param owner The owner of the new position
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param tickLower The lower tick of the position
+ * @param tickUpper The upper tick of the position
+ */
+export async function openPosition(
+  program: anchor.Program<PtSolProgram>,
+  owner: Keypair,
+  pool: PublicKey,
+  tickLower: number,
+  tickUpper: number
+) {
+  return await program.methods
+    .openPosition(tickLower, tickUpper)
+    .accounts({
+      pool,
+      position: getPositionAddress(
+        program,
+        pool,
+        owner.publicKey,
+        tickLower,
+        tickUpper
+      ),
+      owner: owner.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([owner])
+    .rpc();
+}
+
+async function modifyLiquidityAccounts(
+  program: anchor.Program<PtSolProgram>,
+  owner: PublicKey,
+  pool: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  tickSpacing: number,
+  tickLower: number,
+  tickUpper: number
+) {
+  const tokenProgramA = await getTokenProgramId(program.provider.connection, mintA);
+  const tokenProgramB = await getTokenProgramId(program.provider.connection, mintB);
+  return {
+    pool,
+    position: getPositionAddress(program, pool, owner, tickLower, tickUpper),
+    tickArrayLower: getTickArrayAddress(
+      program,
+      pool,
+      getTickArrayStart(tickLower, tickSpacing)
+    ),
+    tickArrayUpper: getTickArrayAddress(
+      program,
+      pool,
+      getTickArrayStart(tickUpper, tickSpacing)
+    ),
+    mintA,
+    mintB,
+    poolTokenAccountA: getAssociatedTokenAddressSync(mintA, pool, true, tokenProgramA),
+    poolTokenAccountB: getAssociatedTokenAddressSync(mintB, pool, true, tokenProgramB),
+    ownerTokenAccountA: getAssociatedTokenAddressSync(mintA, owner, false, tokenProgramA),
+    ownerTokenAccountB: getAssociatedTokenAddressSync(mintB, owner, false, tokenProgramB),
+    owner,
+    tokenProgramA,
+    tokenProgramB,
+    associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+  };
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `increase_liquidity` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param owner The owner of the position
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param mintA The mint address of asset A
+ * @param mintB The mint address of asset B
+ * @param tickSpacing The tick spacing of the pool
+ * @param tickLower The lower tick of the position
+ * @param tickUpper The upper tick of the position
+ * @param liquidity The liquidity to add
+ * @param amountAMax The highest raw amount of asset A to pay
+ * @param amountBMax The highest raw amount of asset B to pay
+ */
+export async function increaseLiquidity(
+  program: anchor.Program<PtSolProgram>,
+  owner: Keypair,
+  pool: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  tickSpacing: number,
+  tickLower: number,
+  tickUpper: number,
+  liquidity: bigint,
+  amountAMax: bigint,
+  amountBMax: bigint
+) {
+  return await program.methods
+    .increaseLiquidity(
+      new anchor.BN(liquidity.toString()),
+      new anchor.BN(amountAMax.toString()),
+      new anchor.BN(amountBMax.toString())
+    )
+    .accounts(
+      await modifyLiquidityAccounts(
+        program,
+        owner.publicKey,
+        pool,
+        mintA,
+        mintB,
+        tickSpacing,
+        tickLower,
+        tickUpper
+      )
+    )
+    .signers([owner])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `decrease_liquidity` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param owner The owner of the position
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param mintA The mint address of asset A
+ * @param mintB The mint address of asset B
+ * @param tickSpacing The tick spacing of the pool
+ * @param tickLower The lower tick of the position
+ * @param tickUpper The upper tick of the position
+ * @param liquidity The liquidity to remove
+ * @param amountAMin The lowest raw amount of asset A to accept
+ * @param amountBMin The lowest raw amount of asset B to accept
+ */
+export async function decreaseLiquidity(
+  program: anchor.Program<PtSolProgram>,
+  owner: Keypair,
+  pool: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  tickSpacing: number,
+  tickLower: number,
+  tickUpper: number,
+  liquidity: bigint,
+  amountAMin: bigint,
+  amountBMin: bigint
+) {
+  return await program.methods
+    .decreaseLiquidity(
+      new anchor.BN(liquidity.toString()),
+      new anchor.BN(amountAMin.toString()),
+      new anchor.BN(amountBMin.toString())
+    )
+    .accounts(
+      await modifyLiquidityAccounts(
+        program,
+        owner.publicKey,
+        pool,
+        mintA,
+        mintB,
+        tickSpacing,
+        tickLower,
+        tickUpper
+      )
+    )
+    .signers([owner])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `collect_fees` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param owner The owner of the position
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param mintA The mint address of asset A
+ * @param mintB The mint address of asset B
+ * @param tickSpacing The tick spacing of the pool
+ * @param tickLower The lower tick of the position
+ * @param tickUpper The upper tick of the position
+ */
+export async function collectFees(
+  program: anchor.Program<PtSolProgram>,
+  owner: Keypair,
+  pool: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  tickSpacing: number,
+  tickLower: number,
+  tickUpper: number
+) {
+  return await program.methods
+    .collectFees()
+    .accounts(
+      await modifyLiquidityAccounts(
+        program,
+        owner.publicKey,
+        pool,
+        mintA,
+        mintB,
+        tickSpacing,
+        tickLower,
+        tickUpper
+      )
+    )
+    .signers([owner])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `close_position` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param owner The owner of the position
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param tickLower The lower tick of the position
+ * @param tickUpper The upper tick of the position
+ */
+export async function closePosition(
+  program: anchor.Program<PtSolProgram>,
+  owner: Keypair,
+  pool: PublicKey,
+  tickLower: number,
+  tickUpper: number
+) {
+  return await program.methods
+    .closePosition()
+    .accounts({
+      position: getPositionAddress(
+        program,
+        pool,
+        owner.publicKey,
+        tickLower,
+        tickUpper
+      ),
+      owner: owner.publicKey,
+    })
+    .signers([owner])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `swap_concentrated` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The user commencing the swap
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param mintA The mint address of asset A
+ * @param mintB The mint address of asset B
+ * @param amountIn The raw amount of the pay asset
+ * @param minAmountOut The lowest raw amount of the receive asset to accept
+ * @param aToB Whether asset A is paid for asset B
+ * @param tickArrays The tick arrays the price moves through, starting with
+ * the one containing the current tick
+ */
+export async function swapConcentrated(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey,
+  amountIn: bigint,
+  minAmountOut: bigint,
+  aToB: boolean,
+  tickArrays: PublicKey[]
+) {
+  const tokenProgramA = await getTokenProgramId(program.provider.connection, mintA);
+  const tokenProgramB = await getTokenProgramId(program.provider.connection, mintB);
+  return await program.methods
+    .swapConcentrated(
+      new anchor.BN(amountIn.toString()),
+      new anchor.BN(minAmountOut.toString()),
+      aToB
+    )
+    .accounts({
+      pool,
+      mintA,
+      mintB,
+      poolTokenAccountA: getAssociatedTokenAddressSync(mintA, pool, true, tokenProgramA),
+      poolTokenAccountB: getAssociatedTokenAddressSync(mintB, pool, true, tokenProgramB),
+      payerTokenAccountA: getAssociatedTokenAddressSync(
+        mintA,
+        payer.publicKey,
+        false,
+        tokenProgramA
+      ),
+      payerTokenAccountB: getAssociatedTokenAddressSync(
+        mintB,
+        payer.publicKey,
+        false,
+        tokenProgramB
+      ),
+      payer: payer.publicKey,
+      tokenProgramA,
+      tokenProgramB,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    })
+    .remainingAccounts(
+      tickArrays.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+    )
+    .signers([payer])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `pause_concentrated` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Concentrated Liquidity Pool
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param withdrawOnly Whether owners can still withdraw liquidity and collect fees
+ */
+export async function pauseConcentrated(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  withdrawOnly: boolean
+) {
+  return await program.methods
+    .pauseConcentrated(withdrawOnly)
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `unpause_concentrated` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Concentrated Liquidity Pool
+ * @param pool The address of the Concentrated Liquidity Pool
+ */
+export async function unpauseConcentrated(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey
+) {
+  return await program.methods
+    .unpauseConcentrated()
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `set_concentrated_protocol_fee` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Concentrated Liquidity Pool
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param protocolFeeBps The protocol's share of the swap fees, in basis points
+ * @param treasury The account allowed to collect the protocol fees
+ */
+export async function setConcentratedProtocolFee(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  protocolFeeBps: number,
+  treasury: PublicKey
+) {
+  return await program.methods
+    .setConcentratedProtocolFee(protocolFeeBps, treasury)
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `collect_concentrated_protocol_fees` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param treasury The treasury of the Concentrated Liquidity Pool
+ * @param pool The address of the Concentrated Liquidity Pool
+ * @param mintA The mint address of asset A
+ * @param mintB The mint address of asset B
+ */
+export async function collectConcentratedProtocolFees(
+  program: anchor.Program<PtSolProgram>,
+  treasury: Keypair,
+  pool: PublicKey,
+  mintA: PublicKey,
+  mintB: PublicKey
+) {
+  const tokenProgramA = await getTokenProgramId(program.provider.connection, mintA);
+  const tokenProgramB = await getTokenProgramId(program.provider.connection, mintB);
+  return await program.methods
+    .collectConcentratedProtocolFees()
+    .accounts({
+      pool,
+      mintA,
+      mintB,
+      poolTokenAccountA: getAssociatedTokenAddressSync(mintA, pool, true, tokenProgramA),
+      poolTokenAccountB: getAssociatedTokenAddressSync(mintB, pool, true, tokenProgramB),
+      treasuryTokenAccountA: getAssociatedTokenAddressSync(
+        mintA,
+        treasury.publicKey,
+        false,
+        tokenProgramA
+      ),
+      treasuryTokenAccountB: getAssociatedTokenAddressSync(
+        mintB,
+        treasury.publicKey,
+        false,
+        tokenProgramB
+      ),
+      treasury: treasury.publicKey,
+      tokenProgramA,
+      tokenProgramB,
+    })
+    .signers([treasury])
+    .rpc();
+}
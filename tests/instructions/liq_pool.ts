@@ -1,10 +1,30 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Keypair, PublicKey } from "@solana/web3.js";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
 import { PtSolProgram } from "../../target/types/pt_sol_program";
-import { toBigIntQuantity } from "../utils/token";
+import { getTokenProgramId, toBigIntQuantity } from "../utils/token";
 import { calculateK, fetchPool, fetchPoolTokenAccounts } from "../utils/swap";
 
+/**
+ *
+ * Derives the address of a Liquidity Pool's LP share mint
+ *
+ * @param program The PtSol program as an `anchor.Program<PtSolProgram>`
+ * @param poolAddress The address of the Liquidity Pool program-derived address account
+ */
+export function getLpMintAddress(
+  program: anchor.Program<PtSolProgram>,
+  poolAddress: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("lp_mint"), poolAddress.toBuffer()],
+    program.programId
+  )[0];
+}
+
 /**
  *
  * Sends a transaction containing the instruction for the pt-sol-program's
@@ -13,25 +33,293 @@ import { calculateK, fetchPool, fetchPoolTokenAccounts } from "../utils/swap";
  * @param program The PtSol program as an `anchor.Program<PtSolProgram>`
  * @param payer The Liquidity Provider (local wallet in `Anchor.toml`)
  * @param poolAddress The address of the Liquidity Pool program-derived address account
+ * @param poolId The id the Liquidity Pool address is derived from
+ * @param feeBps The swap fee of the pool in basis points
+ * @param curve The pricing curve of the pool, `{ constantProduct: {} }` or `{ stableSwap: {} }`
+ * @param amp The amplification coefficient of a StableSwap pool
  */
 export async function createPool(
   program: anchor.Program<PtSolProgram>,
   payer: PublicKey,
   payer_secret: Uint8Array,
-  poolAddress: PublicKey
+  poolAddress: PublicKey,
+  poolId: number,
+  feeBps: number = 100,
+  curve: object = { constantProduct: {} },
+  amp: number = 0
 ) {
   let payerKeypair = Keypair.fromSecretKey(payer_secret);
   return await program.methods
-    .createPool()
+    .createPool(new anchor.BN(poolId), feeBps, curve as any, new anchor.BN(amp))
     .accounts({
       pool: poolAddress,
+      lpMint: getLpMintAddress(program, poolAddress),
       payer: payer,
       systemProgram: anchor.web3.SystemProgram.programId,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
     })
     .signers([payerKeypair])
     .rpc();
 }
 
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `set_pool_fee` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param feeBps The new swap fee in basis points
+ */
+export async function setPoolFee(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  feeBps: number
+) {
+  return await program.methods
+    .setPoolFee(feeBps)
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `set_asset_weights` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param weights The target weight of every asset, in the order of `pool.assets`
+ * @param rampEndSlot The slot at which the weights reach `weights`
+ */
+export async function setAssetWeights(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  weights: number[],
+  rampEndSlot: number
+) {
+  return await program.methods
+    .setAssetWeights(
+      weights.map((w) => new anchor.BN(w)),
+      new anchor.BN(rampEndSlot)
+    )
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `ramp_amp` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the StableSwap Liquidity Pool program-derived address account
+ * @param targetAmp The amplification coefficient to ramp to
+ * @param rampEndSlot The slot at which the ramp reaches `targetAmp`
+ */
+export async function rampAmp(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  targetAmp: number,
+  rampEndSlot: number
+) {
+  return await program.methods
+    .rampAmp(new anchor.BN(targetAmp), new anchor.BN(rampEndSlot))
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `propose_pool_authority` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The current authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param newAuthority The proposed new authority, e.g. a multisig
+ */
+export async function proposePoolAuthority(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  newAuthority: PublicKey
+) {
+  return await program.methods
+    .proposePoolAuthority(newAuthority)
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `accept_pool_authority` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param pendingAuthority The authority proposed by `proposePoolAuthority`
+ * @param pool The address of the Liquidity Pool program-derived address account
+ */
+export async function acceptPoolAuthority(
+  program: anchor.Program<PtSolProgram>,
+  pendingAuthority: Keypair,
+  pool: PublicKey
+) {
+  return await program.methods
+    .acceptPoolAuthority()
+    .accounts({ pool, pendingAuthority: pendingAuthority.publicKey })
+    .signers([pendingAuthority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `delist_asset` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param mint The mint address of the asset to delist
+ * @param treasury The treasury of the Liquidity Pool, receiving the leftover protocol fees
+ */
+export async function delistAsset(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  mint: PublicKey,
+  treasury: PublicKey
+) {
+  const tokenProgram = await getTokenProgramId(program.provider.connection, mint);
+  return await program.methods
+    .delistAsset()
+    .accounts({
+      pool,
+      mint,
+      poolTokenAccount: getAssociatedTokenAddressSync(mint, pool, true, tokenProgram),
+      treasuryTokenAccount: getAssociatedTokenAddressSync(mint, treasury, true, tokenProgram),
+      authority: authority.publicKey,
+      tokenProgram,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `pause` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param withdrawOnly Whether users can still withdraw liquidity and unstake
+ */
+export async function pause(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  withdrawOnly: boolean
+) {
+  return await program.methods
+    .pause(withdrawOnly)
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `unpause` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ */
+export async function unpause(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey
+) {
+  return await program.methods
+    .unpause()
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `set_protocol_fee` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param authority The authority of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param protocolFeeBps The protocol share of the swap fee in basis points
+ * @param treasury The account allowed to collect the protocol fees
+ */
+export async function setProtocolFee(
+  program: anchor.Program<PtSolProgram>,
+  authority: Keypair,
+  pool: PublicKey,
+  protocolFeeBps: number,
+  treasury: PublicKey
+) {
+  return await program.methods
+    .setProtocolFee(protocolFeeBps, treasury)
+    .accounts({ pool, authority: authority.publicKey })
+    .signers([authority])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `collect_protocol_fees` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param treasury The treasury of the Liquidity Pool
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param mint The mint address of the asset to collect the fees of
+ */
+export async function collectProtocolFees(
+  program: anchor.Program<PtSolProgram>,
+  treasury: Keypair,
+  pool: PublicKey,
+  mint: PublicKey
+) {
+  const tokenProgram = await getTokenProgramId(program.provider.connection, mint);
+  return await program.methods
+    .collectProtocolFees()
+    .accounts({
+      pool,
+      mint,
+      poolTokenAccount: getAssociatedTokenAddressSync(mint, pool, true, tokenProgram),
+      treasuryTokenAccount: getAssociatedTokenAddressSync(
+        mint,
+        treasury.publicKey,
+        false,
+        tokenProgram
+      ),
+      treasury: treasury.publicKey,
+      tokenProgram,
+    })
+    .signers([treasury])
+    .rpc();
+}
+
 /**
  *
  * Sends a transaction containing the instruction for the pt-sol program's
@@ -43,6 +331,9 @@ export async function createPool(
  * @param mint The address of the mint being funded to the Liquidity Pool
  * @param quantity The quantity to fund of the provided mint
  * @param decimals the decimals of this mint (used to calculate real quantity)
+ * @param threadAddress The pool-funding Thread, `null` when the Liquidity Provider funds directly
+ * @param threadAuthority The authority of the pool-funding Thread
+ * @param listAsset Whether the deposit lists a new asset, which mints no LP shares
  */
 export async function fundPool(
   program: anchor.Program<PtSolProgram>,
@@ -52,17 +343,24 @@ export async function fundPool(
   mint: PublicKey,
   quantity: number,
   decimals: number,
-  threadAddress: PublicKey,
-  threadAuthority: PublicKey
+  threadAddress: PublicKey | null,
+  threadAuthority: PublicKey,
+  listAsset: boolean = false
 ) {
+  const lpMint = getLpMintAddress(program, pool);
+  const tokenProgram = await getTokenProgramId(program.provider.connection, mint);
   let requiredAccount = {
     pool,
     mint,
-    poolTokenAccount: getAssociatedTokenAddressSync(mint, pool, true),
-    payerTokenAccount: getAssociatedTokenAddressSync(mint, payer),
+    poolTokenAccount: getAssociatedTokenAddressSync(mint, pool, true, tokenProgram),
+    payerTokenAccount: getAssociatedTokenAddressSync(mint, payer, false, tokenProgram),
+    lpMint,
+    payerLpTokenAccount: getAssociatedTokenAddressSync(lpMint, payer),
+    poolLpTokenAccount: getAssociatedTokenAddressSync(lpMint, pool, true),
     payer: payer,
     systemProgram: anchor.web3.SystemProgram.programId,
-    tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+    tokenProgram,
+    lpTokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
     associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
     thread: threadAddress,
     threadAuthority: threadAuthority,
@@ -70,11 +368,458 @@ export async function fundPool(
   let payerKeypair = Keypair.fromSecretKey(payer_secret);
 
   await program.methods
-    .fundPool(new anchor.BN(toBigIntQuantity(quantity, decimals).toString()))
+    .fundPool(new anchor.BN(toBigIntQuantity(quantity, decimals).toString()), listAsset)
     .accounts(requiredAccount)
     .signers([payerKeypair])
     .rpc();
 }
 
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `withdraw_liquidity` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The Liquidity Provider burning their shares
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param shares The raw amount of LP shares to burn
+ */
+export async function withdrawLiquidity(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  shares: bigint
+) {
+  const lpMint = getLpMintAddress(program, pool);
+  const poolData = await fetchPool(program, pool);
+  // (mint, pool token account, payer token account) for every asset, in pool order
+  const remainingAccounts = [];
+  for (const mint of poolData.assets as PublicKey[]) {
+    const tokenProgram = await getTokenProgramId(program.provider.connection, mint);
+    remainingAccounts.push(
+      { pubkey: mint, isSigner: false, isWritable: false },
+      {
+        pubkey: getAssociatedTokenAddressSync(mint, pool, true, tokenProgram),
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: getAssociatedTokenAddressSync(mint, payer.publicKey, false, tokenProgram),
+        isSigner: false,
+        isWritable: true,
+      }
+    );
+  }
+
+  return await program.methods
+    .withdrawLiquidity(new anchor.BN(shares.toString()))
+    .accounts({
+      pool,
+      lpMint,
+      payerLpTokenAccount: getAssociatedTokenAddressSync(lpMint, payer.publicKey),
+      payer: payer.publicKey,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    })
+    .remainingAccounts(remainingAccounts)
+    .signers([payer])
+    .rpc();
+}
 
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `swap` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The user commencing the swap
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param receiveMint The mint address of the asset to receive
+ * @param payMint The mint address of the asset to pay
+ * @param amountToSwap The raw amount of the pay asset
+ * @param minAmountOut The lowest raw amount of the receive asset to accept
+ */
+export async function swap(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  receiveMint: PublicKey,
+  payMint: PublicKey,
+  amountToSwap: bigint,
+  minAmountOut: bigint
+) {
+  const receiveTokenProgram = await getTokenProgramId(
+    program.provider.connection,
+    receiveMint
+  );
+  const payTokenProgram = await getTokenProgramId(program.provider.connection, payMint);
+  return await program.methods
+    .swap(
+      new anchor.BN(amountToSwap.toString()),
+      new anchor.BN(minAmountOut.toString())
+    )
+    .accounts({
+      pool,
+      receiveMint,
+      poolReceiveTokenAccount: getAssociatedTokenAddressSync(
+        receiveMint,
+        pool,
+        true,
+        receiveTokenProgram
+      ),
+      payerReceiveTokenAccount: getAssociatedTokenAddressSync(
+        receiveMint,
+        payer.publicKey,
+        false,
+        receiveTokenProgram
+      ),
+      payMint,
+      poolPayTokenAccount: getAssociatedTokenAddressSync(payMint, pool, true, payTokenProgram),
+      payerPayTokenAccount: getAssociatedTokenAddressSync(
+        payMint,
+        payer.publicKey,
+        false,
+        payTokenProgram
+      ),
+      payer: payer.publicKey,
+      receiveTokenProgram,
+      payTokenProgram,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc();
+}
 
+/**
+ *
+ * Simulates the pt-sol program's `quote_swap` instruction and reads the
+ * quote from its return data. The amounts are exactly what `swap` would
+ * transfer for the same pool state
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param receiveMint The mint address of the asset to receive
+ * @param payMint The mint address of the asset to pay
+ * @param payAmount The raw amount of the pay asset
+ * @returns The receive amount, the fee and the price impact in basis points
+ */
+export async function quoteSwap(
+  program: anchor.Program<PtSolProgram>,
+  pool: PublicKey,
+  receiveMint: PublicKey,
+  payMint: PublicKey,
+  payAmount: bigint
+): Promise<{ receiveAmount: bigint; feeAmount: bigint; priceImpactBps: number }> {
+  const receiveTokenProgram = await getTokenProgramId(
+    program.provider.connection,
+    receiveMint
+  );
+  const payTokenProgram = await getTokenProgramId(program.provider.connection, payMint);
+  const quote = await program.methods
+    .quoteSwap(new anchor.BN(payAmount.toString()))
+    .accounts({
+      pool,
+      receiveMint,
+      poolReceiveTokenAccount: getAssociatedTokenAddressSync(
+        receiveMint,
+        pool,
+        true,
+        receiveTokenProgram
+      ),
+      payMint,
+      poolPayTokenAccount: getAssociatedTokenAddressSync(payMint, pool, true, payTokenProgram),
+      receiveTokenProgram,
+      payTokenProgram,
+    })
+    .view();
+  return {
+    receiveAmount: BigInt(quote.receiveAmount.toString()),
+    feeAmount: BigInt(quote.feeAmount.toString()),
+    priceImpactBps: quote.priceImpactBps.toNumber(),
+  };
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `swap_exact_out` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The user commencing the swap
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param receiveMint The mint address of the asset to receive
+ * @param payMint The mint address of the asset to pay
+ * @param amountOut The raw amount of the receive asset
+ * @param maxAmountIn The highest raw amount of the pay asset to pay
+ */
+export async function swapExactOut(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  receiveMint: PublicKey,
+  payMint: PublicKey,
+  amountOut: bigint,
+  maxAmountIn: bigint
+) {
+  const receiveTokenProgram = await getTokenProgramId(
+    program.provider.connection,
+    receiveMint
+  );
+  const payTokenProgram = await getTokenProgramId(program.provider.connection, payMint);
+  return await program.methods
+    .swapExactOut(
+      new anchor.BN(amountOut.toString()),
+      new anchor.BN(maxAmountIn.toString())
+    )
+    .accounts({
+      pool,
+      receiveMint,
+      poolReceiveTokenAccount: getAssociatedTokenAddressSync(
+        receiveMint,
+        pool,
+        true,
+        receiveTokenProgram
+      ),
+      payerReceiveTokenAccount: getAssociatedTokenAddressSync(
+        receiveMint,
+        payer.publicKey,
+        false,
+        receiveTokenProgram
+      ),
+      payMint,
+      poolPayTokenAccount: getAssociatedTokenAddressSync(payMint, pool, true, payTokenProgram),
+      payerPayTokenAccount: getAssociatedTokenAddressSync(
+        payMint,
+        payer.publicKey,
+        false,
+        payTokenProgram
+      ),
+      payer: payer.publicKey,
+      receiveTokenProgram,
+      payTokenProgram,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `swap_route` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The user commencing the swap
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param path The mint addresses to swap through, starting with the pay asset
+ * and ending with the receive asset
+ * @param amountIn The raw amount of the first asset to pay
+ * @param minAmountOut The lowest raw amount of the last asset to accept
+ */
+export async function swapRoute(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  path: PublicKey[],
+  amountIn: bigint,
+  minAmountOut: bigint
+) {
+  const tokenPrograms = await Promise.all(
+    path.map((mint) => getTokenProgramId(program.provider.connection, mint))
+  );
+  return await program.methods
+    .swapRoute(
+      new anchor.BN(amountIn.toString()),
+      new anchor.BN(minAmountOut.toString())
+    )
+    .accounts({
+      pool,
+      payerPayTokenAccount: getAssociatedTokenAddressSync(
+        path[0],
+        payer.publicKey,
+        false,
+        tokenPrograms[0]
+      ),
+      payerReceiveTokenAccount: getAssociatedTokenAddressSync(
+        path[path.length - 1],
+        payer.publicKey,
+        false,
+        tokenPrograms[path.length - 1]
+      ),
+      payer: payer.publicKey,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+    })
+    .remainingAccounts(
+      path.flatMap((mint, i) => [
+        { pubkey: mint, isSigner: false, isWritable: false },
+        {
+          pubkey: getAssociatedTokenAddressSync(mint, pool, true, tokenPrograms[i]),
+          isSigner: false,
+          isWritable: true,
+        },
+      ])
+    )
+    .signers([payer])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `flash_loan` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param borrower The user taking the flash loan
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param mint The mint address of the asset to borrow
+ * @param amount The raw amount of the asset to borrow
+ * @param borrowerProgram The program invoked with the borrowed amount, which
+ * must repay it plus the fee before returning
+ * @param data The instruction data passed to `borrowerProgram`
+ * @param accounts The accounts passed to `borrowerProgram`
+ */
+export async function flashLoan(
+  program: anchor.Program<PtSolProgram>,
+  borrower: Keypair,
+  pool: PublicKey,
+  mint: PublicKey,
+  amount: bigint,
+  borrowerProgram: PublicKey,
+  data: Buffer,
+  accounts: anchor.web3.AccountMeta[]
+) {
+  const tokenProgram = await getTokenProgramId(
+    program.provider.connection,
+    mint
+  );
+  return await program.methods
+    .flashLoan(new anchor.BN(amount.toString()), data)
+    .accounts({
+      pool,
+      mint,
+      poolTokenAccount: getAssociatedTokenAddressSync(
+        mint,
+        pool,
+        true,
+        tokenProgram
+      ),
+      borrowerTokenAccount: getAssociatedTokenAddressSync(
+        mint,
+        borrower.publicKey,
+        false,
+        tokenProgram
+      ),
+      borrower: borrower.publicKey,
+      borrowerProgram,
+      tokenProgram,
+    })
+    .remainingAccounts(accounts)
+    .signers([borrower])
+    .rpc();
+}
+
+/**
+ *
+ * Derives the address of the TWAP oracle of an asset pair
+ *
+ * @param program The PtSol program as an `anchor.Program<PtSolProgram>`
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param baseMint The mint address of the asset being priced
+ * @param quoteMint The mint address of the asset the price is quoted in
+ */
+export function getPriceOracleAddress(
+  program: anchor.Program<PtSolProgram>,
+  pool: PublicKey,
+  baseMint: PublicKey,
+  quoteMint: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("price_oracle"),
+      pool.toBuffer(),
+      baseMint.toBuffer(),
+      quoteMint.toBuffer(),
+    ],
+    program.programId
+  )[0];
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `initialize_oracle` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The rent payer
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param baseMint The mint address of the asset being priced
+ * @param quoteMint The mint address of the asset the price is quoted in
+ */
+export async function initializeOracle(
+  program: anchor.Program<PtSolProgram>,
+  payer: Keypair,
+  pool: PublicKey,
+  baseMint: PublicKey,
+  quoteMint: PublicKey
+) {
+  return await program.methods
+    .initializeOracle()
+    .accounts({
+      pool,
+      baseMint,
+      quoteMint,
+      oracle: getPriceOracleAddress(program, pool, baseMint, quoteMint),
+      payer: payer.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc();
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `update_oracle` instruction
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param oracle The address of the TWAP oracle
+ */
+export async function updateOracle(
+  program: anchor.Program<PtSolProgram>,
+  pool: PublicKey,
+  oracle: PublicKey
+) {
+  return await program.methods
+    .updateOracle()
+    .accounts({ pool, oracle })
+    .rpc();
+}
+
+/**
+ *
+ * Simulates the pt-sol program's `get_twap` instruction and reads the
+ * time-weighted average price from its return data
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param pool The address of the Liquidity Pool program-derived address account
+ * @param oracle The address of the TWAP oracle
+ * @param windowSlots The length of the averaging window in slots
+ * @returns The average price of the base asset in the quote asset, scaled by 1e18
+ */
+export async function getTwap(
+  program: anchor.Program<PtSolProgram>,
+  pool: PublicKey,
+  oracle: PublicKey,
+  windowSlots: number
+): Promise<bigint> {
+  const price = await program.methods
+    .getTwap(new anchor.BN(windowSlots))
+    .accounts({ pool, oracle })
+    .view();
+  return BigInt(price.toString());
+}
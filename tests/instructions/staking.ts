@@ -18,6 +18,8 @@ export async function stake(
     threadId: String,
     poolAddress: PublicKey,
     amount: BN,
+    // `StakingPool::LOCKUPS`中锁仓选项的编号, 0为不锁仓
+    lockup: number = 0,
 ) {
     // 创建user的token账户
     let userTokenAccount = await getOrCreateAssociatedTokenAccount(
@@ -56,7 +58,13 @@ export async function stake(
   
       console.log("threadAddress: ", threadAddress);
   
+      let [stakingPool] = PublicKey.findProgramAddressSync(
+        [Buffer.from("staking_pool")],
+        program.programId
+      );
+
       let requiredAccount = {
+        stakingPool: stakingPool,
         stakeInfoAccount: stakeInfo,
         stakeAccount: stakeAccount,
         userTokenAccount: userTokenAccount.address,
@@ -77,7 +85,7 @@ export async function stake(
       };
   
       const tx = await program.methods
-        .stake(amount, Buffer.from(threadId))
+        .stake(amount, Buffer.from(threadId), lockup)
         .signers([payer.payer])
         .accounts(requiredAccount)
         .rpc();
@@ -85,4 +93,57 @@ export async function stake(
   
       // await waitForThreadExec(clockworkProvider, threadAddress);
       console.log("Your transaction signature", tx);
-}
\ No newline at end of file
+}
+
+/**
+ *
+ * Sends a transaction containing the instruction for the pt-sol program's
+ * `claim_rewards` instruction, leaving the stake in place
+ *
+ * @param program The pt-sol program as an `anchor.Program<PtSolProgram>`
+ * @param payer The staker claiming their rewards
+ * @param pool The liquidity pool of the staking pool
+ * @param rewardMint The reward mint of the staking pool
+ */
+export async function claimRewards(
+    program: anchor.Program<PtSolProgram>,
+    payer: Wallet,
+    pool: PublicKey,
+    rewardMint: PublicKey,
+) {
+    let [stakingPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_pool")],
+      program.programId
+    );
+
+    let [vaultAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault")],
+      program.programId
+    );
+
+    let [stakeInfo] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_info"), payer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .claimRewards()
+      .signers([payer.payer])
+      .accounts({
+        signer: payer.publicKey,
+        stakingPool: stakingPool,
+        tokenVaultAccount: vaultAccount,
+        stakeInfoAccount: stakeInfo,
+        pool: pool,
+        rewardMint: rewardMint,
+        userRewardTokenAccount: getAssociatedTokenAddressSync(
+          rewardMint,
+          payer.publicKey
+        ),
+        rewardTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log("Your transaction signature", tx);
+}
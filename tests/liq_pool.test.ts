@@ -1,16 +1,38 @@
 import * as anchor from "@coral-xyz/anchor";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { getAccount, getAssociatedTokenAddressSync, getMint } from "@solana/spl-token";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
 import type { PtSolProgram } from "../target/types/pt_sol_program";
 import fs from "fs"
 import assetsConfig from "./utils/assets.json";
-import { createPool, fundPool } from "./instructions/liq_pool";
-import { calculateK, fetchPool, fetchPoolTokenAccounts } from "./utils/swap";
-import { logPool } from "./utils/token";
+import {
+  createPool,
+  fundPool,
+  getLpMintAddress,
+  quoteSwap,
+  swap,
+  swapExactOut,
+  withdrawLiquidity,
+} from "./instructions/liq_pool";
+import { initToken, mintTokens } from "./instructions/tokens";
+import { calculateK, determineSwapPay, fetchPool, fetchPoolTokenAccounts } from "./utils/swap";
+import { logPool, toBigIntQuantity } from "./utils/token";
 
 // Seed prefix for the Liquidity Pool from our program
 const LIQUIDITY_POOL_SEED_PREFIX = "liquidity_pool";
 
+// Id of the Liquidity Pool used by the tests
+const POOL_ID = 0;
+
+// Metaplex Constants
+const METADATA_SEED = "metadata";
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+// `init_token`铸造的token都是9位小数
+const DECIMALS = 9;
+
 // Util function to sleep
 const sleepSeconds = async (s: number) =>
   await new Promise((f) => setTimeout(f, s * 1000));
@@ -23,14 +45,18 @@ function getRandomInt(max: number): number {
 describe("LiquidityPool", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
-  const payer = (provider.wallet as anchor.Wallet).payer;
+  const wallet = provider.wallet as anchor.Wallet;
+  const payer = wallet.payer;
   const program = anchor.workspace.PtSolProgram as anchor.Program<PtSolProgram>;
 
   // connection
   const connection = new Connection("http://127.0.0.1:8899", "confirmed");
 
   const poolAddress = PublicKey.findProgramAddressSync(
-    [Buffer.from(LIQUIDITY_POOL_SEED_PREFIX)],
+    [
+      Buffer.from(LIQUIDITY_POOL_SEED_PREFIX),
+      new anchor.BN(POOL_ID).toArrayLike(Buffer, "le", 8),
+    ],
     program.programId
   )[0];
 
@@ -66,11 +92,187 @@ describe("LiquidityPool", () => {
    */
   it("CreatePool", async () => {
     if (!programInitialized) {
-      const tx = await createPool(program, payer.publicKey, payer.secretKey, poolAddress);
+      const tx = await createPool(program, payer.publicKey, payer.secretKey, poolAddress, POOL_ID);
       console.log("Your transaction signature", tx);
     }
   });
 
+  // 直接存入流动性, 不需要Thread
+  const [threadAuthority] = PublicKey.findProgramAddressSync(
+    [Buffer.from("authority")],
+    program.programId
+  );
+
+  // swap和存取流动性测试使用的两种资产, 权重相同
+  const mintA = Keypair.generate();
+  const mintB = Keypair.generate();
+
+  // payer持有的`mint`的余额
+  async function getBalance(mint: PublicKey): Promise<bigint> {
+    const tokenAccount = getAssociatedTokenAddressSync(mint, payer.publicKey);
+    return (await getAccount(connection, tokenAccount)).amount;
+  }
+
+  // 池子中`mint`属于流动性提供者的储备
+  async function getReserve(mint: PublicKey): Promise<bigint> {
+    const pool = await fetchPool(program, poolAddress);
+    const index = (pool.assets as PublicKey[]).findIndex((m) => m.equals(mint));
+    return BigInt(pool.reserves[index].toString());
+  }
+
+  for (const [mintKeypair, symbol] of [
+    [mintA, "SWAPA"],
+    [mintB, "SWAPB"],
+  ] as [Keypair, string][]) {
+    it(`Fund Pool with token ${symbol}: 1000 ${symbol}`, async () => {
+      const [metadataAddress] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from(METADATA_SEED),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          mintKeypair.publicKey.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      const metadata = {
+        name: `Swap ${symbol}`,
+        symbol,
+        uri: "https://arweave.net/NmBFKuCWk4yfAB4a6EGwXlWM92q5B9nBZMlRhpVA4y4",
+      };
+      await initToken(program, wallet, mintKeypair.publicKey, mintKeypair.secretKey, metadata, metadataAddress);
+      // 一半存入池子, 一半留给payer用于swap
+      await mintTokens(program, wallet, mintKeypair.publicKey, new anchor.BN(2000));
+      await fundPool(
+        program,
+        payer.publicKey,
+        payer.secretKey,
+        poolAddress,
+        mintKeypair.publicKey,
+        1000,
+        DECIMALS,
+        null,
+        threadAuthority,
+        true
+      );
+    });
+  }
+
+  it("Swap pays exactly the quoted amount", async () => {
+    const amountIn = toBigIntQuantity(10, DECIMALS);
+    const quote = await quoteSwap(program, poolAddress, mintB.publicKey, mintA.publicKey, amountIn);
+    const payBefore = await getBalance(mintA.publicKey);
+    const receiveBefore = await getBalance(mintB.publicKey);
+
+    // min_amount_out等于报价时正好成交
+    await swap(program, payer, poolAddress, mintB.publicKey, mintA.publicKey, amountIn, quote.receiveAmount);
+
+    assert.equal(payBefore - (await getBalance(mintA.publicKey)), amountIn);
+    assert.equal((await getBalance(mintB.publicKey)) - receiveBefore, quote.receiveAmount);
+  });
+
+  it("Swap fails when min_amount_out is above the quote", async () => {
+    const amountIn = toBigIntQuantity(10, DECIMALS);
+    const quote = await quoteSwap(program, poolAddress, mintB.publicKey, mintA.publicKey, amountIn);
+    const payBefore = await getBalance(mintA.publicKey);
+
+    try {
+      await swap(program, payer, poolAddress, mintB.publicKey, mintA.publicKey, amountIn, quote.receiveAmount + 1n);
+      assert.fail("swap should fail when min_amount_out is above the quote");
+    } catch (e) {
+      assert.instanceOf(e, anchor.AnchorError);
+      assert.equal((e as anchor.AnchorError).error.errorCode.code, "InvalidSwapSlippageExceeded");
+    }
+    // 交易失败, payer的余额不变
+    assert.equal(await getBalance(mintA.publicKey), payBefore);
+  });
+
+  it("SwapExactOut pays exactly the quoted amount", async () => {
+    const amountOut = toBigIntQuantity(5, DECIMALS);
+    const pool = await fetchPool(program, poolAddress);
+    // 两种资产权重相同, 报价与程序的`determine_swap_pay`完全一致
+    const quotedPay = determineSwapPay(
+      await getReserve(mintA.publicKey),
+      await getReserve(mintB.publicKey),
+      amountOut,
+      BigInt(pool.feeBps)
+    );
+    const payBefore = await getBalance(mintB.publicKey);
+    const receiveBefore = await getBalance(mintA.publicKey);
+
+    // max_amount_in等于报价时正好成交
+    await swapExactOut(program, payer, poolAddress, mintA.publicKey, mintB.publicKey, amountOut, quotedPay);
+
+    assert.equal(payBefore - (await getBalance(mintB.publicKey)), quotedPay);
+    assert.equal((await getBalance(mintA.publicKey)) - receiveBefore, amountOut);
+  });
+
+  it("SwapExactOut fails when max_amount_in is below the quote", async () => {
+    const amountOut = toBigIntQuantity(5, DECIMALS);
+    const pool = await fetchPool(program, poolAddress);
+    const quotedPay = determineSwapPay(
+      await getReserve(mintA.publicKey),
+      await getReserve(mintB.publicKey),
+      amountOut,
+      BigInt(pool.feeBps)
+    );
+
+    try {
+      await swapExactOut(program, payer, poolAddress, mintA.publicKey, mintB.publicKey, amountOut, quotedPay - 1n);
+      assert.fail("swap_exact_out should fail when max_amount_in is below the quote");
+    } catch (e) {
+      assert.instanceOf(e, anchor.AnchorError);
+      assert.equal((e as anchor.AnchorError).error.errorCode.code, "InvalidSwapMaxAmountInExceeded");
+    }
+  });
+
+  it("Deposit and withdraw round trip", async () => {
+    const lpMint = getLpMintAddress(program, poolAddress);
+    const depositAmount = toBigIntQuantity(100, DECIMALS);
+    const lpBefore = await getBalance(lpMint);
+    const supplyBefore = (await getMint(connection, lpMint)).supply;
+    const balanceBefore = await getBalance(mintA.publicKey);
+
+    await fundPool(
+      program,
+      payer.publicKey,
+      payer.secretKey,
+      poolAddress,
+      mintA.publicKey,
+      100,
+      DECIMALS,
+      null,
+      threadAuthority
+    );
+
+    // 铸造的份额全部给了payer
+    const shares = (await getBalance(lpMint)) - lpBefore;
+    const supply = (await getMint(connection, lpMint)).supply;
+    assert.isTrue(shares > 0n);
+    assert.equal(supply - supplyBefore, shares);
+    assert.equal(balanceBefore - (await getBalance(mintA.publicKey)), depositAmount);
+
+    // 取回的每一种资产按份额占总供应量的比例计算, 向下取整
+    const pool = await fetchPool(program, poolAddress);
+    const assets = pool.assets as PublicKey[];
+    const expected = [];
+    const before = [];
+    for (const mint of assets) {
+      expected.push(((await getReserve(mint)) * shares) / supply);
+      before.push(await getBalance(mint));
+    }
+
+    await withdrawLiquidity(program, payer, poolAddress, shares);
+
+    for (const [i, mint] of assets.entries()) {
+      assert.equal((await getBalance(mint)) - before[i], expected[i], `payout of ${mint.toBase58()}`);
+    }
+    assert.equal(await getBalance(lpMint), lpBefore);
+    assert.equal((await getMint(connection, lpMint)).supply, supplyBefore);
+
+    // 单边存入需要支付手续费, 取回的资产A不会多于存入的数额
+    const indexA = assets.findIndex((m) => m.equals(mintA.publicKey));
+    assert.isTrue(expected[indexA] < depositAmount);
+  });
+
   async function getPoolData(log: boolean): Promise<bigint> {
     const pool = await fetchPool(program, poolAddress)
     const poolTokenAccounts = await fetchPoolTokenAccounts(
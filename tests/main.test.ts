@@ -2,3 +2,4 @@
 require("./tokens.test");
 require("./liq_pool.test");
 require("./staking.test");
+require("./concentrated.test");
@@ -47,8 +47,14 @@ describe("test", () => {
   // Seed prefix for the Liquidity Pool from our program
   const LIQUIDITY_POOL_SEED_PREFIX = "liquidity_pool";
 
+  // Id of the Liquidity Pool used by the tests
+  const POOL_ID = 0;
+
   const poolAddress = PublicKey.findProgramAddressSync(
-    [Buffer.from(LIQUIDITY_POOL_SEED_PREFIX)],
+    [
+      Buffer.from(LIQUIDITY_POOL_SEED_PREFIX),
+      new anchor.BN(POOL_ID).toArrayLike(Buffer, "le", 8),
+    ],
     program.programId
   )[0];
 
@@ -188,17 +194,37 @@ describe("test", () => {
       program.programId
     );
 
+    // 全局的stake配置
+    let [stakingPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_pool")],
+      program.programId
+    );
+
+    // 本程序的ProgramData账户, 只有其中记录的升级权限可以初始化stake
+    let [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
     // 将账户存放里面
     let initAccount = {
       signer: payer.publicKey,
+      program: program.programId,
+      programData: programData,
       tokenVaultAccount: vaultAccount,
-      mint: mintKeypair.publicKey,
+      stakingPool: stakingPool,
+      // stake对应的流动性池子, 池子暂停时不能stake、unstake和领取奖励
+      pool: poolAddress,
+      // stake和奖励都使用PETERSOL
+      stakingMint: mintKeypair.publicKey,
+      rewardMint: mintKeypair.publicKey,
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: anchor.web3.SystemProgram.programId,
     };
 
     const tx = await program.methods
-      .initializeStaking()
+      // 每个slot发放1个token的奖励, 按stake数额分给所有用户
+      .initializeStaking(new anchor.BN(10).pow(new anchor.BN(9)))
       // 对应合约里面initialize里面的字段
       .accounts(initAccount)
       .rpc();
@@ -244,7 +270,13 @@ describe("test", () => {
 
     console.log("threadAddress: ", threadAddress);
 
+    let [stakingPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_pool")],
+      program.programId
+    );
+
     let requiredAccount = {
+      stakingPool: stakingPool,
       stakeInfoAccount: stakeInfo,
       stakeAccount: stakeAccount,
       userTokenAccount: userTokenAccount.address,
@@ -265,7 +297,8 @@ describe("test", () => {
     };
 
     const tx = await program.methods
-      .stake(new anchor.BN(100), Buffer.from(threadId))
+      // 不锁仓, 之后的测试需要unstake
+      .stake(new anchor.BN(100), Buffer.from(threadId), 0)
       .signers([payer.payer])
       .accounts(requiredAccount)
       .rpc();
@@ -287,7 +320,8 @@ describe("test", () => {
           50,
           3,
           threadAddress,
-          threadAuthority
+          threadAuthority,
+          true
       )
   })
 
@@ -320,7 +354,8 @@ describe("test", () => {
             a[5],
             a[4],
             threadAddress,
-            threadAuthority
+            threadAuthority,
+            true
         )
     })
   }
@@ -359,14 +394,24 @@ describe("test", () => {
       1e21
     );
 
+    let [stakingPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("staking_pool")],
+      program.programId
+    );
+
     let requiredAccount = {
       tokenVaultAccount: vaultAccount,
+      stakingPool: stakingPool,
       stakeInfoAccount: stakeInfo,
       userTokenAccount: userTokenAccount.address,
       stakeAccount: stakeAccount,
+      pool: poolAddress,
       signer: payer.publicKey,
       mint: mintKeypair.publicKey,
       tokenProgram: TOKEN_PROGRAM_ID,
+      rewardMint: mintKeypair.publicKey,
+      userRewardTokenAccount: userTokenAccount.address,
+      rewardTokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
       systemProgram: anchor.web3.SystemProgram.programId,
       thread: threadAddress,
@@ -375,7 +420,8 @@ describe("test", () => {
     };
 
     const tx = await program.methods
-      .unstake()
+      // 全部unstake, 与stake的数额相同
+      .unstake(new anchor.BN(100))
       .signers([payer.payer])
       .accounts(requiredAccount)
       .rpc();
@@ -139,4 +139,80 @@ export function calculateChangeInK(start: bigint, end: bigint): string {
     const change = endNum - startNum
     const percentChange = (change / startNum) * 100
     return percentChange.toFixed(4) + '%'
-}
\ No newline at end of file
+}
+/**
+ *
+ * Mirrors the program's `determine_swap_receive` for two assets of equal
+ * weight using `bigint` math, so quotes match the on-chain result exactly.
+ * For any pool use `quoteSwap`, which simulates the program itself
+ *
+ * @param poolReceiveBalance The pool's balance of the asset to receive
+ * @param poolPayBalance The pool's balance of the asset to pay
+ * @param payAmount The amount of the asset to pay
+ * @param feeBps The swap fee in basis points
+ * @returns The amount of the asset the user will receive
+ */
+export function determineSwapReceive(
+    poolReceiveBalance: bigint,
+    poolPayBalance: bigint,
+    payAmount: bigint,
+    feeBps: bigint = 100n
+): bigint {
+    const pGamma = payAmount * (10_000n - feeBps)
+    return (poolReceiveBalance * pGamma) / (poolPayBalance * 10_000n + pGamma)
+}
+
+/**
+ *
+ * Mirrors the program's `determine_swap_pay` for two assets of equal weight
+ * using `bigint` math: the amount the user has to pay to receive exactly
+ * `receiveAmount`
+ *
+ * @param poolReceiveBalance The pool's balance of the asset to receive
+ * @param poolPayBalance The pool's balance of the asset to pay
+ * @param receiveAmount The amount of the asset to receive
+ * @param feeBps The swap fee in basis points
+ * @returns The amount of the asset the user will pay
+ */
+export function determineSwapPay(
+    poolReceiveBalance: bigint,
+    poolPayBalance: bigint,
+    receiveAmount: bigint,
+    feeBps: bigint = 100n
+): bigint {
+    const numerator = poolPayBalance * receiveAmount * 10_000n
+    const denominator = (poolReceiveBalance - receiveAmount) * (10_000n - feeBps)
+    return (numerator + denominator - 1n) / denominator
+}
+
+/**
+ *
+ * Mirrors the program's `swap_route` for assets of equal weight: chains
+ * `determineSwapReceive` along the path, moving the pool balances after
+ * every hop
+ *
+ * @param poolBalances The pool's balance of each asset on the path, in path order
+ * @param amountIn The amount of the first asset to pay
+ * @param feeBps The swap fee in basis points
+ * @returns The amount of the last asset the user will receive
+ */
+export function determineRouteReceive(
+    poolBalances: bigint[],
+    amountIn: bigint,
+    feeBps: bigint = 100n
+): bigint {
+    let amount = amountIn
+    // The previous hop already paid `amount` of this asset out of the pool
+    let paidOut = 0n
+    for (let i = 0; i + 1 < poolBalances.length; i++) {
+        const receiveAmount = determineSwapReceive(
+            poolBalances[i + 1],
+            poolBalances[i] - paidOut,
+            amount,
+            feeBps
+        )
+        paidOut = receiveAmount
+        amount = receiveAmount
+    }
+    return amount
+}
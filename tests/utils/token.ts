@@ -169,6 +169,22 @@ export async function mintNewTokens(
   );
 }
 
+/**
+ *
+ * Returns the token program that owns a mint, either SPL Token or Token-2022
+ *
+ * @param connection Connection to Solana RPC
+ * @param mint The asset's mint address
+ * @returns The program ID of the mint's token program
+ */
+export async function getTokenProgramId(
+  connection: Connection,
+  mint: PublicKey
+): Promise<PublicKey> {
+  const mintAccount = await connection.getAccountInfo(mint);
+  return mintAccount.owner;
+}
+
 /**
  *
  * Returns the real quantity of a `quantity` parameter by